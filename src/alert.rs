@@ -0,0 +1,182 @@
+//! Module to assist in alerting on ruuvitag readings crossing thresholds.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::alert::{subscribe_with_alerts, AlertRule};
+//!
+//! // Replace with your mac address.
+//! let mac = "CC:6F:70:EE:4C:AD";
+//! let rules = [AlertRule::TemperatureAbove(30.0), AlertRule::BatteryLow(2500)];
+//! let alerts = subscribe_with_alerts(&mac, &rules, |rule, sensor_data| {
+//!     println!("{}: {rule:?}", sensor_data.mac_as_str());
+//! })
+//! .await?;
+//! alerts.await?;
+//! ```
+use crate::error::RuuviError;
+use crate::ruuvitag::{subscribe_ruuvitag, SensorDataV5};
+use tokio::task::JoinHandle;
+
+/// Conditions a reading is checked against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertRule {
+    /// Temperature above the given °C.
+    TemperatureAbove(f64),
+    /// Temperature below the given °C.
+    TemperatureBelow(f64),
+    /// Relative humidity above the given %.
+    HumidityAbove(f64),
+    /// Relative humidity below the given %.
+    HumidityBelow(f64),
+    /// Battery voltage below the given mV.
+    BatteryLow(u16),
+}
+
+impl AlertRule {
+    /// Returns `true` if `sensor_data` violates the rule.
+    ///
+    /// `None` if the value the rule checks is reported as not available.
+    pub fn is_violated(&self, sensor_data: &SensorDataV5) -> Option<bool> {
+        match *self {
+            AlertRule::TemperatureAbove(limit) => sensor_data
+                .try_temperature_in_celcius()
+                .map(|temperature| temperature > limit),
+            AlertRule::TemperatureBelow(limit) => sensor_data
+                .try_temperature_in_celcius()
+                .map(|temperature| temperature < limit),
+            AlertRule::HumidityAbove(limit) => sensor_data
+                .try_get_humidity()
+                .map(|humidity| humidity > limit),
+            AlertRule::HumidityBelow(limit) => sensor_data
+                .try_get_humidity()
+                .map(|humidity| humidity < limit),
+            AlertRule::BatteryLow(threshold_mv) => Some(sensor_data.battery_is_low(threshold_mv)),
+        }
+    }
+}
+
+/// Checks readings against a set of rules and reports when they cross into violation.
+///
+/// A rule is reported once when a reading violates it after a reading that didn't, not on every
+/// reading while it stays violated. The rule is reported again after it has recovered. Readings
+/// where the checked value is not available leave the state of the rule unchanged. Violations of
+/// the first reading are reported.
+#[derive(Debug, Clone)]
+pub struct AlertMonitor {
+    /// The rules and whether they were violated by the latest reading.
+    rules: Vec<(AlertRule, bool)>,
+}
+
+impl AlertMonitor {
+    /// Constructs an `AlertMonitor` checking `rules`.
+    pub fn new(rules: &[AlertRule]) -> Self {
+        Self {
+            rules: rules.iter().map(|rule| (*rule, false)).collect(),
+        }
+    }
+
+    /// Feeds a reading to the monitor and returns the rules it newly violates.
+    pub fn observe(&mut self, sensor_data: &SensorDataV5) -> Vec<AlertRule> {
+        let mut crossed = Vec::new();
+        for (rule, violated) in self.rules.iter_mut() {
+            let now_violated = match rule.is_violated(sensor_data) {
+                Some(now_violated) => now_violated,
+                None => continue,
+            };
+            if now_violated && !*violated {
+                crossed.push(*rule);
+            }
+            *violated = now_violated;
+        }
+        crossed
+    }
+}
+
+/// Subscribes to a ruuvitag and calls `on_alert` when a reading crosses one of `rules`.
+///
+/// `on_alert` is called with the crossed rule and the reading, debounced as described in
+/// [`AlertMonitor`]. The readings are consumed by the returned task, which runs until the
+/// subscription ends. Abort the task to stop the subscription.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let rules = [AlertRule::HumidityBelow(20.0)];
+/// let alerts = subscribe_with_alerts(&mac, &rules, |rule, sensor_data| {
+///     println!("{}: {rule:?}", sensor_data.mac_as_str());
+/// })
+/// .await?;
+/// alerts.await?;
+/// ```
+pub async fn subscribe_with_alerts<F>(
+    mac_address: &str,
+    rules: &[AlertRule],
+    mut on_alert: F,
+) -> Result<JoinHandle<()>, RuuviError>
+where
+    F: FnMut(&AlertRule, &SensorDataV5) + Send + 'static,
+{
+    let mut rx = subscribe_ruuvitag(mac_address).await?;
+    let mut monitor = AlertMonitor::new(rules);
+    Ok(tokio::spawn(async move {
+        while let Some(sensor_data) = rx.recv().await {
+            for rule in monitor.observe(&sensor_data) {
+                on_alert(&rule, &sensor_data);
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::alert::{AlertMonitor, AlertRule};
+    use crate::ruuvitag::SensorField;
+    use crate::testing::PayloadBuilder;
+
+    #[test]
+    fn test_alert_monitor_fires_on_crossing() {
+        let above = AlertRule::TemperatureAbove(30.0);
+        let mut monitor = AlertMonitor::new(&[above, AlertRule::HumidityBelow(20.0)]);
+        let normal = PayloadBuilder::spec_example()
+            .temperature(25.0)
+            .sensor_data();
+        let hot = PayloadBuilder::spec_example()
+            .temperature(31.0)
+            .sensor_data();
+        // 25 °C, then 31 °C twice.
+        assert!(monitor.observe(&normal).is_empty());
+        assert_eq!(monitor.observe(&hot), vec![above]);
+        assert!(monitor.observe(&hot).is_empty());
+        // An unavailable temperature doesn't reset the rule.
+        let invalid = PayloadBuilder::spec_example()
+            .not_available(SensorField::Temperature)
+            .sensor_data();
+        assert!(monitor.observe(&invalid).is_empty());
+        assert!(monitor.observe(&hot).is_empty());
+        // Recovered, then crossed again.
+        assert!(monitor.observe(&normal).is_empty());
+        assert_eq!(monitor.observe(&hot), vec![above]);
+    }
+
+    #[test]
+    fn test_alert_rule_battery_low() {
+        // 2977 mV.
+        let sensor_data = PayloadBuilder::spec_example().sensor_data();
+        assert_eq!(
+            AlertRule::BatteryLow(3100).is_violated(&sensor_data),
+            Some(true)
+        );
+        assert_eq!(
+            AlertRule::BatteryLow(2500).is_violated(&sensor_data),
+            Some(false)
+        );
+        let mut monitor = AlertMonitor::new(&[AlertRule::BatteryLow(3100)]);
+        assert_eq!(
+            monitor.observe(&sensor_data),
+            vec![AlertRule::BatteryLow(3100)]
+        );
+    }
+}
@@ -12,32 +12,59 @@
 //!     "org.bluez",
 //!     // Replace AA_BB_CC_DD_EE_FF with your mac address you are connecting to.
 //!     format!("/org/bluez/hci0/AA_BB_CC_DD_EE_FF"),
-//!     Duration::from_millis(20),
+//!     Duration::from_secs(5),
 //! );
 //!
 //! let _id = proxy.match_signal(
 //!     move |h: PropertiesPropertiesChanged, _: &Connection, _: &Message| {
-//!         let tag_data =
-//!             SensorDataV5::from_dbus_changed_properties(h.changed_properties).unwrap();
-//!         // Do something here with tag data.
+//!         if let Ok(tag_data) = SensorDataV5::from_dbus_changed_properties(h.changed_properties) {
+//!             // Do something here with tag data.
+//!         }
 //!         true
 //!     },
 //! );
 //!
-//! conn.process(Duration::from_millis(100)).unwrap();
+//! loop {
+//!     conn.process(Duration::from_millis(100))?;
+//! }
 //! ```
+use crate::error::RuuviError;
+use crate::mac::MacAddress;
+use crate::ruuvitag::has_ruuvi_manufacturer_data;
 use dbus::arg;
+use dbus::blocking::stdintf::org_freedesktop_dbus::{ObjectManager, Properties};
 use dbus::blocking::Connection;
-use std::error::Error;
-use std::time::Duration;
+use dbus::nonblock::stdintf::org_freedesktop_dbus::Properties as NonblockProperties;
+use dbus::nonblock::{self, SyncConnection};
+use std::io;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Name of the bluetooth adapter used unless configured otherwise.
+pub const DEFAULT_ADAPTER: &str = "hci0";
+/// Timeout of the D-Bus method calls to bluez unless configured otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long to wait for a device to be discovered.
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long to wait for bluez to resolve the GATT services of a connected device.
+const SERVICES_RESOLVED_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Connects to a dbus bluetooth service.
 ///
-/// Powers on and returns a connection to a dbus bluetooth service (bluez) and connects to hci0 interface.
+/// Returns a connection to the dbus bluetooth service (bluez) that has powered on the adapter
+/// `DEFAULT_ADAPTER` and started discovery on it. Use `connect_bluetooth_with_adapter` for
+/// another adapter.
+///
+/// The discovery started on the adapter is stopped when the returned `BluetoothConnection` is
+/// dropped.
 ///
-/// # Panics
+/// # Errors
 ///
-/// If the given interface or service doesn't exist on the machine.
+/// `RuuviError::DBus` if the system bus can't be connected to, the adapter doesn't exist, or
+/// powering it on or starting discovery fails.
 ///
 /// # Examples
 ///
@@ -51,30 +78,790 @@ use std::time::Duration;
 ///     "org.bluez",
 ///     // Replace AA_BB_CC_DD_EE_FF with your mac address you are connecting to.
 ///     format!("/org/bluez/hci0/AA_BB_CC_DD_EE_FF"),
-///     Duration::from_millis(20),
+///     Duration::from_secs(5),
 /// );
 ///
 /// let _id = proxy.match_signal(
 ///     move |h: PropertiesPropertiesChanged, _: &Connection, _: &Message| {
-///         let tag_data =
-///             SensorDataV5::from_dbus_changed_properties(h.changed_properties).unwrap();
-///         // Do something here with tag data.
+///         if let Ok(tag_data) = SensorDataV5::from_dbus_changed_properties(h.changed_properties) {
+///             // Do something here with tag data.
+///         }
 ///         true
 ///     },
 /// );
 ///
-/// conn.process(Duration::from_millis(100)).unwrap();
+/// loop {
+///     conn.process(Duration::from_millis(100))?;
+/// }
+/// ```
+pub fn connect_bluetooth() -> Result<BluetoothConnection, RuuviError> {
+    connect_bluetooth_with_adapter(DEFAULT_ADAPTER)
+}
+
+/// Connects to a dbus bluetooth service using the bluetooth adapter `adapter`.
+///
+/// Works like `connect_bluetooth`, but powers on and starts discovery on the given adapter, e.g.
+/// `hci1` of a USB dongle, instead of `hci0`.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::connect_bluetooth_with_adapter;
+///
+/// let conn = connect_bluetooth_with_adapter("hci1")?;
+/// ```
+pub fn connect_bluetooth_with_adapter(adapter: &str) -> Result<BluetoothConnection, RuuviError> {
+    connect_bluetooth_with_options(&ConnectOptions {
+        adapter: adapter.to_string(),
+        ..ConnectOptions::default()
+    })
+}
+
+/// Options of a connection to bluez.
+///
+/// # Timeouts
+///
+/// `timeout` limits how long the method calls setting up the connection, e.g. powering on the
+/// adapter and starting discovery, wait for bluez to reply. It has nothing to do with how often
+/// readings are received: the connections of the subscriptions are driven by a tokio task and
+/// the signals of the tags are delivered as soon as they arrive. With the blocking
+/// `BluetoothConnection` returned by `connect_bluetooth_with_options` the signals are dispatched
+/// by `Connection::process`, whose own timeout is the poll interval of the loop calling it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectOptions {
+    /// Name of the bluetooth adapter, `DEFAULT_ADAPTER` by default.
+    pub adapter: String,
+    /// Timeout of the setup method calls, `DEFAULT_TIMEOUT` by default.
+    pub timeout: Duration,
+    /// Filter set with `SetDiscoveryFilter` before starting discovery, the default
+    /// `DiscoveryFilter` by default. `None` leaves the discovery unfiltered.
+    pub discovery_filter: Option<DiscoveryFilter>,
+    /// Powers on the adapter if it is off, `true` by default.
+    ///
+    /// The `Powered` property is read first, so an adapter that is on already isn't written to.
+    /// Set to `false` where the bluetooth power is managed elsewhere or changing it isn't
+    /// permitted. Discovery fails then if the adapter is off.
+    pub power_on: bool,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self {
+            adapter: DEFAULT_ADAPTER.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+            discovery_filter: Some(DiscoveryFilter::default()),
+            power_on: true,
+        }
+    }
+}
+
+/// Discovery filter of bluez restricting which devices are reported during discovery.
+///
+/// Without a filter bluez reports every bluetooth device in range, which wakes the process on
+/// every advertisement in a crowded environment. Ruuvitags are Bluetooth Low Energy devices, so
+/// the default filter discovers only `le` devices. Bluez can't filter by manufacturer data, and
+/// ruuvitags don't advertise service UUIDs in their measurement advertisements, so the rest of
+/// the filtering happens when decoding.
+///
+/// The filter is set for the D-Bus connection. Bluez merges the filters of all of its clients,
+/// so other applications scanning at the same time may widen it.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::{connect_bluetooth_with_options, ConnectOptions, DiscoveryFilter};
+///
+/// // Ignore tags further away than about -80 dBm.
+/// let conn = connect_bluetooth_with_options(&ConnectOptions {
+///     discovery_filter: Some(DiscoveryFilter {
+///         rssi: Some(-80),
+///         ..DiscoveryFilter::default()
+///     }),
+///     ..ConnectOptions::default()
+/// })?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryFilter {
+    /// Transport to discover on: `le`, `bredr` or `auto`. `le` by default.
+    pub transport: String,
+    /// Minimum RSSI in dBm of the reported advertisements, `None` by default.
+    pub rssi: Option<i16>,
+    /// Service UUIDs of which at least one has to be advertised, empty by default.
+    pub uuids: Vec<String>,
+    /// Reports every advertisement, including the ones repeating the previous data, `false` by
+    /// default.
+    ///
+    /// Ruuvitags repeat each measurement in several advertisements. With `false` bluez reports
+    /// only the advertisements whose data changed, which keeps the CPU usage down. Set it to
+    /// `true` to receive every advertisement, e.g. for logging acceleration at the full rate.
+    pub duplicate_data: bool,
+}
+
+impl Default for DiscoveryFilter {
+    fn default() -> Self {
+        Self {
+            transport: "le".to_string(),
+            rssi: None,
+            uuids: Vec::new(),
+            duplicate_data: false,
+        }
+    }
+}
+
+impl DiscoveryFilter {
+    /// Returns the filter as the argument of `SetDiscoveryFilter`.
+    fn properties(&self) -> arg::PropMap {
+        let mut properties = arg::PropMap::new();
+        properties.insert(
+            "Transport".to_string(),
+            arg::Variant(Box::new(self.transport.clone())),
+        );
+        properties.insert(
+            "DuplicateData".to_string(),
+            arg::Variant(Box::new(self.duplicate_data)),
+        );
+        if let Some(rssi) = self.rssi {
+            properties.insert("RSSI".to_string(), arg::Variant(Box::new(rssi)));
+        }
+        if !self.uuids.is_empty() {
+            properties.insert(
+                "UUIDs".to_string(),
+                arg::Variant(Box::new(self.uuids.clone())),
+            );
+        }
+        properties
+    }
+}
+
+/// Connects to a dbus bluetooth service with the given `options`.
+///
+/// Works like `connect_bluetooth_with_adapter`, but the timeout of the setup method calls can be
+/// configured as well.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::{connect_bluetooth_with_options, ConnectOptions};
+/// use std::time::Duration;
+///
+/// let conn = connect_bluetooth_with_options(&ConnectOptions {
+///     timeout: Duration::from_secs(1),
+///     ..ConnectOptions::default()
+/// })?;
+/// loop {
+///     // Poll for signals every 100 ms.
+///     conn.process(Duration::from_millis(100))?;
+/// }
+/// ```
+pub fn connect_bluetooth_with_options(
+    options: &ConnectOptions,
+) -> Result<BluetoothConnection, RuuviError> {
+    let conn = Connection::new_system()?;
+    let adapter_path = adapter_path(&options.adapter);
+    power_on_adapter(&conn, &adapter_path, options)?;
+    Ok(BluetoothConnection {
+        conn,
+        adapter_path,
+        timeout: options.timeout,
+        discovering: true,
+    })
+}
+
+/// Blocking connection to bluez that has started discovery on an adapter.
+///
+/// Dereferences to the D-Bus `Connection`, so it is used like one. Dropping the connection stops
+/// the discovery, so the adapter isn't left scanning after the program is done with it. Use
+/// `stop` to handle the errors of stopping the discovery.
+pub struct BluetoothConnection {
+    conn: Connection,
+    adapter_path: String,
+    timeout: Duration,
+    discovering: bool,
+}
+
+impl BluetoothConnection {
+    /// Stops the discovery and closes the connection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ruuviscanner::bluetooth::connect_bluetooth;
+    ///
+    /// let conn = connect_bluetooth()?;
+    /// // Do something here with the connection.
+    /// conn.stop()?;
+    /// ```
+    pub fn stop(mut self) -> Result<(), RuuviError> {
+        Ok(self.stop_discovery()?)
+    }
+
+    /// Returns the D-Bus path of the adapter discovering on this connection, e.g.
+    /// `/org/bluez/hci0`.
+    pub fn adapter_path(&self) -> &str {
+        &self.adapter_path
+    }
+
+    /// Stops the discovery unless it has been stopped already.
+    fn stop_discovery(&mut self) -> Result<(), dbus::Error> {
+        if !self.discovering {
+            return Ok(());
+        }
+        self.discovering = false;
+        self.conn
+            .with_proxy("org.bluez", &self.adapter_path, self.timeout)
+            .method_call::<(), _, _, _>("org.bluez.Adapter1", "StopDiscovery", ())
+    }
+}
+
+impl Deref for BluetoothConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl Drop for BluetoothConnection {
+    fn drop(&mut self) {
+        let _ = self.stop_discovery();
+    }
+}
+
+/// Connects asynchronously to a dbus bluetooth service.
+///
+/// Works like `connect_bluetooth_with_options`, but returns a connection driven by a tokio task
+/// instead of a connection that has to be polled with `process`. The returned task handles the
+/// I/O of the connection and should be aborted once the connection is no longer needed.
+///
+/// # Examples
+///
 /// ```
-pub fn connect_bluetooth() -> Result<Connection, Box<(dyn Error + 'static)>> {
-    let conn = Connection::new_system().unwrap();
+/// use ruuviscanner::bluetooth::{connect_bluetooth_async, ConnectOptions};
+///
+/// let (conn, resource_task) = connect_bluetooth_async(&ConnectOptions::default()).await?;
+/// // Do something here with the connection.
+/// resource_task.abort();
+/// ```
+pub async fn connect_bluetooth_async(
+    options: &ConnectOptions,
+) -> Result<(Arc<SyncConnection>, JoinHandle<()>), RuuviError> {
+    let (resource, conn) = dbus_tokio::connection::new_system_sync()?;
+    let resource_task = tokio::spawn(async move {
+        let _e = resource.await;
+        #[cfg(feature = "tracing")]
+        tracing::warn!(error = %_e, "lost connection to D-Bus");
+    });
+    let adapter_path = adapter_path(&options.adapter);
+    if let Err(e) = power_on_adapter_async(&conn, &adapter_path, options).await {
+        resource_task.abort();
+        return Err(e.into());
+    }
+    Ok((conn, resource_task))
+}
+
+/// Works like `power_on_adapter` without blocking.
+pub(crate) async fn power_on_adapter_async(
+    conn: &Arc<SyncConnection>,
+    adapter_path: &str,
+    options: &ConnectOptions,
+) -> Result<(), dbus::Error> {
     let set_bluetooth_on_proxy =
-        conn.with_proxy("org.bluez", "/org/bluez/hci0", Duration::from_millis(5000));
-
-    set_bluetooth_on_proxy.method_call(
-        "org.freedesktop.DBus.Properties",
-        "Set",
-        ("org.bluez.Adapter1", "Powered", arg::Variant(true)),
-    )?;
-    set_bluetooth_on_proxy.method_call("org.bluez.Adapter1", "StartDiscovery", ())?;
-    Ok(conn)
+        nonblock::Proxy::new("org.bluez", adapter_path, options.timeout, Arc::clone(conn));
+
+    if options.power_on
+        && !NonblockProperties::get::<bool>(
+            &set_bluetooth_on_proxy,
+            "org.bluez.Adapter1",
+            "Powered",
+        )
+        .await?
+    {
+        set_bluetooth_on_proxy
+            .method_call::<(), _, _, _>(
+                "org.freedesktop.DBus.Properties",
+                "Set",
+                ("org.bluez.Adapter1", "Powered", arg::Variant(true)),
+            )
+            .await?;
+    }
+    if let Some(filter) = &options.discovery_filter {
+        set_bluetooth_on_proxy
+            .method_call::<(), _, _, _>(
+                "org.bluez.Adapter1",
+                "SetDiscoveryFilter",
+                (filter.properties(),),
+            )
+            .await?;
+    }
+    set_bluetooth_on_proxy
+        .method_call::<(), _, _, _>("org.bluez.Adapter1", "StartDiscovery", ())
+        .await
+}
+
+/// Returns the bluez D-Bus path of the bluetooth adapter `adapter`, e.g. `/org/bluez/hci0`.
+pub(crate) fn adapter_path(adapter: &str) -> String {
+    format!("/org/bluez/{adapter}")
+}
+
+/// Stops the discovery started on the adapter in `adapter_path` over `conn`.
+pub(crate) async fn stop_discovery_async(
+    conn: &Arc<SyncConnection>,
+    adapter_path: &str,
+    timeout: Duration,
+) -> Result<(), dbus::Error> {
+    nonblock::Proxy::new("org.bluez", adapter_path, timeout, Arc::clone(conn))
+        .method_call::<(), _, _, _>("org.bluez.Adapter1", "StopDiscovery", ())
+        .await
+}
+
+/// Powers on the adapter in `adapter_path` unless it is on already or `options` skip it, and
+/// starts discovery on it with the discovery filter of `options`.
+fn power_on_adapter(
+    conn: &Connection,
+    adapter_path: &str,
+    options: &ConnectOptions,
+) -> Result<(), dbus::Error> {
+    let set_bluetooth_on_proxy = conn.with_proxy("org.bluez", adapter_path, options.timeout);
+
+    if options.power_on && !set_bluetooth_on_proxy.get::<bool>("org.bluez.Adapter1", "Powered")? {
+        set_bluetooth_on_proxy.method_call::<(), _, _, _>(
+            "org.freedesktop.DBus.Properties",
+            "Set",
+            ("org.bluez.Adapter1", "Powered", arg::Variant(true)),
+        )?;
+    }
+    if let Some(filter) = &options.discovery_filter {
+        set_bluetooth_on_proxy.method_call::<(), _, _, _>(
+            "org.bluez.Adapter1",
+            "SetDiscoveryFilter",
+            (filter.properties(),),
+        )?;
+    }
+    set_bluetooth_on_proxy.method_call::<(), _, _, _>("org.bluez.Adapter1", "StartDiscovery", ())
+}
+
+/// Powers on every bluetooth adapter and starts discovery on them.
+///
+/// Reads all of the `org.bluez.Adapter1` objects from bluez with `GetManagedObjects`, powers them
+/// on and starts discovery with the default `ConnectOptions`. Returns a connection per adapter,
+/// ordered by the index of the adapter, e.g. hci2 before hci10. Bluez keeps discovering only as
+/// long as the client that started the discovery is connected, so the discovery on an adapter
+/// stops when its connection is dropped.
+///
+/// Blocks while talking to bluez, use `power_on_all_adapters_async` from async code.
+///
+/// # Errors
+///
+/// Fails if bluez can't be reached or any of the adapters can't be powered on.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::power_on_all_adapters;
+///
+/// let connections = power_on_all_adapters()?;
+/// for conn in &connections {
+///     println!("{}", conn.adapter_path());
+/// }
+/// ```
+pub fn power_on_all_adapters() -> Result<Vec<BluetoothConnection>, RuuviError> {
+    let mut adapter_paths: Vec<String> = Connection::new_system()?
+        .with_proxy("org.bluez", "/", DEFAULT_TIMEOUT)
+        .get_managed_objects()?
+        .into_iter()
+        .filter(|(_, interfaces)| interfaces.contains_key("org.bluez.Adapter1"))
+        .map(|(path, _)| path.to_string())
+        .collect();
+    adapter_paths.sort_by(|a, b| adapter_order(a).cmp(&adapter_order(b)));
+    let options = ConnectOptions::default();
+    adapter_paths
+        .into_iter()
+        .map(|adapter_path| {
+            let conn = Connection::new_system()?;
+            power_on_adapter(&conn, &adapter_path, &options)?;
+            Ok(BluetoothConnection {
+                conn,
+                adapter_path,
+                timeout: options.timeout,
+                discovering: true,
+            })
+        })
+        .collect()
+}
+
+/// Works like `power_on_all_adapters` on the blocking thread pool of tokio.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::power_on_all_adapters_async;
+///
+/// let connections = power_on_all_adapters_async().await?;
+/// ```
+pub async fn power_on_all_adapters_async() -> Result<Vec<BluetoothConnection>, RuuviError> {
+    tokio::task::spawn_blocking(power_on_all_adapters)
+        .await
+        .map_err(io::Error::from)?
+}
+
+/// Returns the key ordering adapter paths by their name and then by their index, so that
+/// `/org/bluez/hci2` comes before `/org/bluez/hci10`.
+fn adapter_order(adapter_path: &str) -> (&str, u64) {
+    let name = adapter_path.trim_end_matches(|c: char| c.is_ascii_digit());
+    (name, adapter_path[name.len()..].parse().unwrap_or(0))
+}
+
+/// Returns the GATT service UUIDs exposed by a ruuvitag.
+///
+/// Connects to the device with the given `mac_address`, waits for bluez to resolve its GATT
+/// services and returns the UUIDs of all `org.bluez.GattService1` objects under the device. The
+/// services tell which firmware features (e.g. history download over the Nordic UART service)
+/// are available on the tag. The device is disconnected before returning, also when resolving the
+/// services fails. Uses the `DEFAULT_ADAPTER`, see `list_services_with_adapter`.
+///
+/// Note that this requires a connection to the tag, unlike listening to advertisements. While
+/// connected the tag stops advertising, so subscribers won't receive data in the meantime.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::list_services;
+///
+/// // Replace with your mac address.
+/// let services = list_services("CC:6F:70:EE:4C:AD")?;
+/// for uuid in services {
+///     println!("{uuid}");
+/// }
+/// ```
+pub fn list_services(mac_address: &str) -> Result<Vec<String>, RuuviError> {
+    list_services_with_adapter(mac_address, DEFAULT_ADAPTER)
+}
+
+/// Works like `list_services`, connecting to the ruuvitag through the given `adapter`, e.g.
+/// `hci1`.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::list_services_with_adapter;
+///
+/// // Replace with your mac address.
+/// let services = list_services_with_adapter("CC:6F:70:EE:4C:AD", "hci1")?;
+/// ```
+pub fn list_services_with_adapter(
+    mac_address: &str,
+    adapter: &str,
+) -> Result<Vec<String>, RuuviError> {
+    let device_path = mac_address.parse::<MacAddress>()?.device_path(adapter);
+    let conn = connect_bluetooth_with_adapter(adapter)?;
+    let device_proxy = conn.with_proxy("org.bluez", &device_path, DEFAULT_TIMEOUT);
+
+    device_proxy.method_call::<(), _, _, _>("org.bluez.Device1", "Connect", ())?;
+    let services = resolved_services(&conn, &device_path, mac_address);
+    // Disconnect whether or not the services were resolved, the tag doesn't advertise while
+    // connected.
+    let disconnected =
+        device_proxy.method_call::<(), _, _, _>("org.bluez.Device1", "Disconnect", ());
+    let services = services?;
+    disconnected?;
+    Ok(services)
+}
+
+/// Waits for bluez to resolve the GATT services of the connected device in `device_path` and
+/// returns their UUIDs.
+fn resolved_services(
+    conn: &Connection,
+    device_path: &str,
+    mac_address: &str,
+) -> Result<Vec<String>, RuuviError> {
+    let device_proxy = conn.with_proxy("org.bluez", device_path, DEFAULT_TIMEOUT);
+    let started = Instant::now();
+    while !device_proxy.get::<bool>("org.bluez.Device1", "ServicesResolved")? {
+        if started.elapsed() > SERVICES_RESOLVED_TIMEOUT {
+            return Err(RuuviError::Timeout(format!(
+                "Services of {mac_address} were not resolved in time"
+            )));
+        }
+        sleep(Duration::from_millis(100));
+    }
+
+    let object_manager = conn.with_proxy("org.bluez", "/", DEFAULT_TIMEOUT);
+    let objects = object_manager.get_managed_objects()?;
+    let service_prefix = format!("{device_path}/");
+    Ok(objects
+        .iter()
+        .filter(|(path, _)| path.starts_with(&service_prefix))
+        .filter_map(|(_, interfaces)| interfaces.get("org.bluez.GattService1"))
+        .filter_map(|properties| arg::prop_cast::<String>(properties, "UUID").cloned())
+        .collect())
+}
+
+/// Returns the mac addresses of the ruuvitags whose bluez `Name` or `Alias` equals `name`.
+///
+/// Starts discovery and waits 10 seconds for the devices to advertise, so that every tag with the
+/// name is found rather than only the first one to appear. Only devices advertising Ruuvi
+/// manufacturer data (company ID `0x0499`) are considered. Returns an empty list if no tag
+/// matched.
+///
+/// Blocks for the whole discovery, call it with e.g. `tokio::task::spawn_blocking` from async
+/// code.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::find_devices_by_name;
+///
+/// let macs = find_devices_by_name("Ruuvi 4CAD")?;
+/// println!("{macs:?}");
+/// ```
+pub fn find_devices_by_name(name: &str) -> Result<Vec<String>, RuuviError> {
+    let conn = connect_bluetooth()?;
+    sleep(DISCOVERY_TIMEOUT);
+    let object_manager = conn.with_proxy("org.bluez", "/", DEFAULT_TIMEOUT);
+    let mut macs: Vec<String> = object_manager
+        .get_managed_objects()?
+        .values()
+        .filter_map(|interfaces| interfaces.get("org.bluez.Device1"))
+        .filter(|properties| is_ruuvitag_named(properties, name))
+        .filter_map(|properties| arg::prop_cast::<String>(properties, "Address").cloned())
+        .collect();
+    macs.sort();
+    macs.dedup();
+    Ok(macs)
+}
+
+/// Returns `true` if the `org.bluez.Device1` `properties` are of a ruuvitag whose `Name` or
+/// `Alias` equals `name`.
+fn is_ruuvitag_named(properties: &arg::PropMap, name: &str) -> bool {
+    has_ruuvi_manufacturer_data(properties)
+        && ["Name", "Alias"]
+            .iter()
+            .any(|key| arg::prop_cast::<String>(properties, key).map(String::as_str) == Some(name))
+}
+
+/// Name and alias of a device known to bluez.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Name the device advertises, e.g. `Ruuvi 4CAD`.
+    pub name: Option<String>,
+    /// Alias of the device, e.g. a friendly label like `Fridge` set with
+    /// `bluetoothctl set-alias`. Bluez defaults the alias to the name.
+    pub alias: Option<String>,
+}
+
+impl DeviceInfo {
+    /// Returns the alias of the device, or its name if it has no alias.
+    pub fn display_name(&self) -> Option<&str> {
+        self.alias.as_deref().or(self.name.as_deref())
+    }
+
+    /// Reads the name and alias from the `org.bluez.Device1` properties of a device.
+    fn from_properties(properties: &arg::PropMap) -> Self {
+        Self {
+            name: arg::prop_cast::<String>(properties, "Name").cloned(),
+            alias: arg::prop_cast::<String>(properties, "Alias").cloned(),
+        }
+    }
+}
+
+/// Returns the name and alias bluez knows for the device with the given `mac_address`.
+///
+/// The device has to have been discovered by the default adapter, e.g. by subscribing to it.
+///
+/// # Errors
+///
+/// `RuuviError::DeviceNotFound` if bluez doesn't know the device.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::device_info;
+/// use ruuviscanner::ruuvitag::subscribe_ruuvitags;
+///
+/// // Replace with your mac addresses.
+/// let macs = ["C0:CB:4E:3D:3E:12", "CC:6F:70:EE:4C:AD"];
+/// let mut rx = subscribe_ruuvitags(&macs).await?;
+/// while let Some((mac, current_sensor_data)) = rx.recv().await {
+///     let info = device_info(&mac)?;
+///     let name = info.display_name().unwrap_or(&mac);
+///     println!("{name}: {}", current_sensor_data.summary());
+/// }
+/// ```
+pub fn device_info(mac_address: &str) -> Result<DeviceInfo, RuuviError> {
+    let device_path = mac_address
+        .parse::<MacAddress>()?
+        .device_path(DEFAULT_ADAPTER);
+    let conn = Connection::new_system()?;
+    let object_manager = conn.with_proxy("org.bluez", "/", DEFAULT_TIMEOUT);
+    object_manager
+        .get_managed_objects()?
+        .iter()
+        .find(|(path, _)| ***path == *device_path)
+        .and_then(|(_, interfaces)| interfaces.get("org.bluez.Device1"))
+        .map(DeviceInfo::from_properties)
+        .ok_or_else(|| RuuviError::DeviceNotFound(mac_address.to_string()))
+}
+
+/// Returns the mac addresses of the ruuvitags nearby.
+///
+/// Starts discovery, waits for `timeout` for the tags to advertise and returns the addresses of
+/// the devices known to bluez whose manufacturer data contains the Ruuvi company ID `0x0499`. The
+/// addresses are sorted and can be passed on to e.g. `subscribe_ruuvitags`. Scans with the
+/// `DEFAULT_ADAPTER`, see `discover_ruuvitags_with_adapters`.
+///
+/// Blocks for the whole `timeout`, call it with e.g. `tokio::task::spawn_blocking` from async
+/// code.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::discover_ruuvitags;
+/// use std::time::Duration;
+///
+/// let macs = discover_ruuvitags(Duration::from_secs(10))?;
+/// println!("{macs:?}");
+/// ```
+pub fn discover_ruuvitags(timeout: Duration) -> Result<Vec<String>, RuuviError> {
+    discover_ruuvitags_with_adapters(timeout, &[DEFAULT_ADAPTER])
+}
+
+/// Works like `discover_ruuvitags`, scanning with every one of `adapters`, e.g. `hci0` and
+/// `hci1`.
+///
+/// Returns the ruuvitags seen by any of the adapters, a tag heard by several of them is returned
+/// once.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::discover_ruuvitags_with_adapters;
+/// use std::time::Duration;
+///
+/// let macs = discover_ruuvitags_with_adapters(Duration::from_secs(10), &["hci0", "hci1"])?;
+/// println!("{macs:?}");
+/// ```
+pub fn discover_ruuvitags_with_adapters(
+    timeout: Duration,
+    adapters: &[&str],
+) -> Result<Vec<String>, RuuviError> {
+    // The connections keep the adapters discovering until the scan is over.
+    let connections = adapters
+        .iter()
+        .map(|adapter| connect_bluetooth_with_adapter(adapter))
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(conn) = connections.first() else {
+        return Ok(Vec::new());
+    };
+    sleep(timeout);
+    let adapter_prefixes: Vec<String> = connections
+        .iter()
+        .map(|conn| format!("{}/", conn.adapter_path()))
+        .collect();
+    let object_manager = conn.with_proxy("org.bluez", "/", DEFAULT_TIMEOUT);
+    let mut macs: Vec<String> = object_manager
+        .get_managed_objects()?
+        .iter()
+        .filter(|(path, _)| {
+            adapter_prefixes
+                .iter()
+                .any(|prefix| path.starts_with(prefix.as_str()))
+        })
+        .filter_map(|(_, interfaces)| interfaces.get("org.bluez.Device1"))
+        .filter(|properties| has_ruuvi_manufacturer_data(properties))
+        .filter_map(|properties| arg::prop_cast::<String>(properties, "Address").cloned())
+        .collect();
+    macs.sort();
+    macs.dedup();
+    Ok(macs)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::bluetooth::{adapter_order, is_ruuvitag_named, DeviceInfo, DiscoveryFilter};
+    use dbus::arg::{self, PropMap, RefArg, Variant};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_adapter_order() {
+        let mut adapter_paths = ["/org/bluez/hci10", "/org/bluez/hci2", "/org/bluez/hci0"];
+        adapter_paths.sort_by(|a, b| adapter_order(a).cmp(&adapter_order(b)));
+        assert_eq!(
+            adapter_paths,
+            ["/org/bluez/hci0", "/org/bluez/hci2", "/org/bluez/hci10"]
+        );
+    }
+
+    #[test]
+    fn test_discovery_filter_properties() {
+        let properties = DiscoveryFilter::default().properties();
+        assert_eq!(properties.len(), 2);
+        assert_eq!(
+            arg::prop_cast::<String>(&properties, "Transport").map(String::as_str),
+            Some("le")
+        );
+        assert_eq!(
+            arg::prop_cast::<bool>(&properties, "DuplicateData"),
+            Some(&false)
+        );
+
+        let properties = DiscoveryFilter {
+            rssi: Some(-80),
+            uuids: vec!["6e400001-b5a3-f393-e0a9-e50e24dcca9e".to_string()],
+            duplicate_data: true,
+            ..DiscoveryFilter::default()
+        }
+        .properties();
+        assert_eq!(
+            arg::prop_cast::<bool>(&properties, "DuplicateData"),
+            Some(&true)
+        );
+        assert_eq!(arg::prop_cast::<i16>(&properties, "RSSI"), Some(&-80));
+        assert!(properties.contains_key("UUIDs"));
+    }
+
+    #[test]
+    fn test_device_info_display_name() {
+        let mut properties = PropMap::new();
+        properties.insert(
+            "Name".to_string(),
+            Variant(Box::new("Ruuvi 4CAD".to_string())),
+        );
+        let info = DeviceInfo::from_properties(&properties);
+        assert_eq!(info.display_name(), Some("Ruuvi 4CAD"));
+
+        properties.insert("Alias".to_string(), Variant(Box::new("Fridge".to_string())));
+        let info = DeviceInfo::from_properties(&properties);
+        assert_eq!(info.name.as_deref(), Some("Ruuvi 4CAD"));
+        assert_eq!(info.display_name(), Some("Fridge"));
+
+        assert_eq!(
+            DeviceInfo::from_properties(&PropMap::new()).display_name(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_ruuvitag_named() {
+        let properties = |company_id: u16| {
+            let mut manufacturer_data: HashMap<u16, Variant<Box<dyn RefArg>>> = HashMap::new();
+            manufacturer_data.insert(company_id, Variant(Box::new(vec![0x05_u8; 24])));
+            let mut properties = PropMap::new();
+            properties.insert(
+                "ManufacturerData".to_string(),
+                Variant(Box::new(manufacturer_data)),
+            );
+            properties.insert(
+                "Name".to_string(),
+                Variant(Box::new("Ruuvi 4CAD".to_string())),
+            );
+            properties.insert("Alias".to_string(), Variant(Box::new("Fridge".to_string())));
+            properties
+        };
+        assert!(is_ruuvitag_named(&properties(0x0499), "Ruuvi 4CAD"));
+        assert!(is_ruuvitag_named(&properties(0x0499), "Fridge"));
+        assert!(!is_ruuvitag_named(&properties(0x0499), "Sauna"));
+        // Other devices sharing the name aren't ruuvitags.
+        assert!(!is_ruuvitag_named(&properties(0x004C), "Fridge"));
+    }
 }
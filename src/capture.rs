@@ -0,0 +1,154 @@
+//! Module for decoding captured ruuvitag frames offline.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::capture::decode_dir;
+//!
+//! for sensor_data in decode_dir("captures")? {
+//!     match sensor_data {
+//!         Ok(sensor_data) => println!("{}", sensor_data.summary()),
+//!         Err(e) => eprintln!("{e}"),
+//!     }
+//! }
+//! ```
+use crate::error::RuuviError;
+use crate::ruuvitag::{decode_base64, SensorDataV5};
+use std::fs::{self, ReadDir};
+use std::path::Path;
+
+impl SensorDataV5 {
+    /// Constructs `SensorDataV5` from the 24 byte payload encoded as hex.
+    ///
+    /// Whitespace and `:` or `-` separators between the bytes are ignored.
+    pub fn from_hex(hex: &str) -> Result<Self, RuuviError> {
+        let digits: Vec<u8> = hex
+            .bytes()
+            .filter(|x| !x.is_ascii_whitespace() && *x != b':' && *x != b'-')
+            .collect();
+        let pairs = digits.chunks_exact(2);
+        if !pairs.remainder().is_empty() {
+            return Err(RuuviError::Malformed(format!(
+                "Odd number of hex digits in {hex:?}"
+            )));
+        }
+        let bytes = pairs
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .ok_or_else(|| RuuviError::Malformed(format!("Invalid hex digits in {hex:?}")))
+            })
+            .collect::<Result<Vec<u8>, RuuviError>>()?;
+        SensorDataV5::from_raw_bytes(&bytes)
+    }
+
+    /// Constructs `SensorDataV5` from the 24 byte payload encoded as base64.
+    pub fn from_base64(base64: &str) -> Result<Self, RuuviError> {
+        SensorDataV5::from_raw_bytes(&decode_base64(base64)?)
+    }
+}
+
+/// Decodes a single captured frame.
+///
+/// The frame is either the bare payload or a JSON string containing it, encoded as hex or base64.
+fn decode_frame(frame: &str) -> Result<SensorDataV5, RuuviError> {
+    let frame = frame.trim();
+    let frame = frame
+        .strip_prefix('"')
+        .and_then(|x| x.strip_suffix('"'))
+        .unwrap_or(frame);
+    SensorDataV5::from_hex(frame).or_else(|_| SensorDataV5::from_base64(frame))
+}
+
+/// Returns an iterator decoding every file in the directory `path` as a captured frame.
+///
+/// Each file contains a single frame as hex or base64, optionally quoted as a JSON string. Files
+/// are read lazily one at a time. Files that can't be read or decoded are reported as errors of
+/// their own items, the iteration continues with the next file. The order of the files is the
+/// order of the directory listing.
+///
+/// # Examples
+///
+/// ```
+/// for sensor_data in decode_dir("captures")? {
+///     match sensor_data {
+///         Ok(sensor_data) => println!("{}", sensor_data.summary()),
+///         Err(e) => eprintln!("{e}"),
+///     }
+/// }
+/// ```
+pub fn decode_dir<P: AsRef<Path>>(path: P) -> Result<DecodeDir, RuuviError> {
+    Ok(DecodeDir {
+        entries: fs::read_dir(path)?,
+    })
+}
+
+/// Iterator returned by [`decode_dir`].
+#[derive(Debug)]
+pub struct DecodeDir {
+    entries: ReadDir,
+}
+
+impl Iterator for DecodeDir {
+    type Item = Result<SensorDataV5, RuuviError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.entries.next()? {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(e.into())),
+            };
+            let path = entry.path();
+            if path.is_dir() {
+                continue;
+            }
+            return Some(
+                fs::read_to_string(&path)
+                    .map_err(RuuviError::from)
+                    .and_then(|frame| decode_frame(&frame))
+                    .map_err(|e| RuuviError::File {
+                        path,
+                        source: Box::new(e),
+                    }),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::capture::decode_dir;
+    use crate::ruuvitag::SensorDataV5;
+    use std::fs;
+
+    const SPEC_HEX: &str = "0512FC5394C37C0004FFFC040CAC364200CDCBB8334C884F";
+    const SPEC_BASE64: &str = "BRL8U5TDfAAE//wEDKw2QgDNy7gzTIhP";
+
+    #[test]
+    fn test_capture_from_hex_and_base64() {
+        let from_hex = SensorDataV5::from_hex(SPEC_HEX).unwrap();
+        let from_base64 = SensorDataV5::from_base64(SPEC_BASE64).unwrap();
+        assert_eq!(from_hex.to_raw_bytes(), from_base64.to_raw_bytes());
+        assert_eq!(from_hex.mac_as_str(), "CB:B8:33:4C:88:4F");
+        assert!(SensorDataV5::from_hex("05 12 FC").is_err());
+        assert!(SensorDataV5::from_base64("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_capture_decode_dir() {
+        let dir = std::env::temp_dir().join(format!("ruuviscanner-capture-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("hex.json"), format!("\"{SPEC_HEX}\"\n")).unwrap();
+        fs::write(dir.join("base64.json"), SPEC_BASE64).unwrap();
+        fs::write(dir.join("broken.json"), "{}").unwrap();
+
+        let results: Vec<_> = decode_dir(&dir).unwrap().collect();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.iter().filter(|x| x.is_ok()).count(), 2);
+        assert!(results.iter().any(|x| x
+            .as_ref()
+            .is_err_and(|e| e.to_string().contains("broken.json"))));
+    }
+}
@@ -0,0 +1,62 @@
+//! Module containing the error type of ruuviscanner.
+use std::path::PathBuf;
+
+/// Errors returned by ruuviscanner.
+#[derive(Debug, thiserror::Error)]
+pub enum RuuviError {
+    /// The advertisement doesn't contain Ruuvi manufacturer data (company ID `0x0499`).
+    ///
+    /// `found` lists the manufacturers whose data was present instead.
+    #[error("Missing Ruuvi manufacturer data (0x0499), found manufacturers {found:?}")]
+    MissingManufacturerData { found: Vec<String> },
+    /// The advertisement doesn't contain Eddystone service data of a ruuvitag in URL mode.
+    #[error("Missing Eddystone service data")]
+    MissingServiceData,
+    /// The payload has the wrong length for its data format.
+    #[error("Wrong payload length {got}, expected {expected}")]
+    WrongLength { expected: usize, got: usize },
+    /// The payload is of a data format that isn't supported.
+    #[error("Unsupported data format {0}")]
+    UnsupportedFormat(u8),
+    /// The data couldn't be parsed, e.g. malformed AD structures or encodings.
+    #[error("Malformed data: {0}")]
+    Malformed(String),
+    /// The mac address isn't six hex octets separated by `:` or `-`.
+    #[error("Invalid mac address {0:?}, expected e.g. \"CC:6F:70:EE:4C:AD\"")]
+    InvalidMac(String),
+    /// The payload carries the mac address of a different ruuvitag than expected.
+    #[error("Mac address mismatch: expected {expected}, payload is from {got}")]
+    MacMismatch { expected: String, got: String },
+    /// The payload is of a different data format than the one expected of the ruuvitag.
+    #[error("Data format mismatch of {mac}: expected {expected}, got {got}")]
+    FormatMismatch { mac: String, expected: u8, got: u8 },
+    /// No ruuvitag matched the given name.
+    #[error("No ruuvitag named {0:?} found")]
+    DeviceNotFound(String),
+    /// More than one ruuvitag matched the given name.
+    #[error("Multiple ruuvitags named {name:?} found: {macs:?}")]
+    AmbiguousName { name: String, macs: Vec<String> },
+    /// Bluez didn't finish an operation in time.
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    /// The connection to D-Bus was lost.
+    #[error("Lost the connection to D-Bus")]
+    ConnectionLost,
+    /// A D-Bus call failed.
+    #[error("D-Bus error: {0}")]
+    DBus(#[from] dbus::Error),
+    /// Publishing to the MQTT broker failed.
+    #[cfg(feature = "mqtt")]
+    #[error("MQTT error: {0}")]
+    Mqtt(#[from] rumqttc::ClientError),
+    /// An I/O operation failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Decoding the file in `path` failed.
+    #[error("{path:?}: {source}")]
+    File {
+        path: PathBuf,
+        #[source]
+        source: Box<RuuviError>,
+    },
+}
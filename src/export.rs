@@ -0,0 +1,747 @@
+//! Module to assist in exporting ruuvitag data to other systems.
+//!
+//! Exports for third party clients are behind feature flags named after the client crate, e.g.
+//! `influxdb2`.
+#[cfg(any(feature = "influxdb2", feature = "serde"))]
+use crate::error::RuuviError;
+use crate::ruuvitag::SensorDataV5;
+#[cfg(feature = "serde")]
+use crate::ruuvitag::{
+    Acceleration, BATTERY_OFFSET, INVALID_ACCELERATION, INVALID_HUMIDITY, INVALID_PRESSURE,
+    INVALID_TEMPERATURE, TX_POWER_OFFSET,
+};
+use std::fmt::Display;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Cayenne LPP channels of the exported values.
+const LPP_CHANNEL_TEMPERATURE: u8 = 1;
+const LPP_CHANNEL_HUMIDITY: u8 = 2;
+const LPP_CHANNEL_PRESSURE: u8 = 3;
+const LPP_CHANNEL_ACCELERATION: u8 = 4;
+/// Cayenne LPP data types.
+const LPP_TYPE_TEMPERATURE: u8 = 0x67;
+const LPP_TYPE_HUMIDITY: u8 = 0x68;
+const LPP_TYPE_BAROMETER: u8 = 0x73;
+const LPP_TYPE_ACCELEROMETER: u8 = 0x71;
+
+/// Returns `value` as a JSON value, `null` if not available.
+fn json_value<T: Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "null".to_string(), |value| value.to_string())
+}
+
+/// Returns `value` as a JSON string.
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Escapes the `special` characters of `value` with a backslash for the InfluxDB line protocol.
+fn escape_influx(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escapes a label value of the Prometheus text exposition format.
+fn escape_prometheus(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Converts a value in thousandths of a unit, e.g. mV, to the unit.
+fn milli_to_unit<T: Into<f64>>(value: T) -> f64 {
+    value.into() / 1000_f64
+}
+
+impl SensorDataV5 {
+    /// Returns the computed values of the sensor data as a JSON object.
+    ///
+    /// The keys match the names used by Ruuvi Station and Home Assistant integrations:
+    ///
+    /// | Key                                                  | Unit |
+    /// |------------------------------------------------------|------|
+    /// | `temperature`                                        | °C   |
+    /// | `humidity`                                           | %    |
+    /// | `pressure`                                           | Pa   |
+    /// | `acceleration_x`, `acceleration_y`, `acceleration_z` | mG   |
+    /// | `battery_voltage`                                    | mV   |
+    /// | `tx_power`                                           | dBm  |
+    /// | `movement_counter`, `measurement_number`, `mac`      |      |
+    /// | `rssi`                                               | dBm  |
+    ///
+    /// Fields the ruuvitag reported as not available are `null`, as is `rssi` if bluez didn't
+    /// report it along with the reading. The keys are stable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.to_json());
+    /// }
+    /// ```
+    pub fn to_json(&self) -> String {
+        self.to_json_with_name(None)
+    }
+
+    /// Returns the computed values of the sensor data as a JSON object like `to_json`, with the
+    /// friendly `name` of the ruuvitag as the `name` key after `mac`.
+    ///
+    /// The `name` key is left out if `name` is `None`. See `TagRegistry::to_json` to look the
+    /// name up by mac address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     // {"mac":"CC:6F:70:EE:4C:AD","name":"Sauna","temperature":24.3,...}
+    ///     println!("{}", current_sensor_data.to_json_with_name(Some("Sauna")));
+    /// }
+    /// ```
+    pub fn to_json_with_name(&self, name: Option<&str>) -> String {
+        let acceleration = self.get_acceleration_in_mg();
+        let name = name.map_or_else(String::new, |name| {
+            format!(",\"name\":{}", json_string(name))
+        });
+        format!(
+            concat!(
+                "{{\"mac\":\"{}\"{},\"temperature\":{},\"humidity\":{},\"pressure\":{},",
+                "\"acceleration_x\":{},\"acceleration_y\":{},\"acceleration_z\":{},",
+                "\"battery_voltage\":{},\"tx_power\":{},\"movement_counter\":{},",
+                "\"measurement_number\":{},\"rssi\":{}}}"
+            ),
+            self.mac_as_str(),
+            name,
+            json_value(self.try_temperature_in_celcius()),
+            json_value(self.try_get_humidity()),
+            json_value(self.try_get_pressure()),
+            json_value(acceleration.try_x()),
+            json_value(acceleration.try_y()),
+            json_value(acceleration.try_z()),
+            self.get_battery_voltage(),
+            self.get_tx_power(),
+            self.movement_counter(),
+            self.measurement_number(),
+            json_value(self.rssi())
+        )
+    }
+
+    /// Returns the sensor data as a line of the InfluxDB line protocol of the given `measurement`.
+    ///
+    /// The mac address is stored as the `mac` tag and the computed values as fields, with the
+    /// same names and types as `to_influx_point` so both can write to the same measurement.
+    /// Fields the ruuvitag reported as not available are left out. The line ends with
+    /// `timestamp` in nanoseconds, or without a timestamp if `None` so the server assigns one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::SystemTime;
+    ///
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     // ruuvi,mac=CC:6F:70:EE:4C:AD temperature=24.3,humidity=53.49,pressure=100044i,...
+    ///     println!("{}", current_sensor_data.to_influx_line("ruuvi", Some(SystemTime::now())));
+    /// }
+    /// ```
+    pub fn to_influx_line(&self, measurement: &str, timestamp: Option<SystemTime>) -> String {
+        let acceleration = self.get_acceleration_in_mg();
+        let mut fields = Vec::new();
+        if let Some(temperature) = self.try_temperature_in_celcius() {
+            fields.push(format!("temperature={temperature}"));
+        }
+        if let Some(humidity) = self.try_get_humidity() {
+            fields.push(format!("humidity={humidity}"));
+        }
+        if let Some(pressure) = self.try_get_pressure() {
+            fields.push(format!("pressure={pressure}i"));
+        }
+        for (name, axis) in [
+            ("acceleration_x", acceleration.try_x()),
+            ("acceleration_y", acceleration.try_y()),
+            ("acceleration_z", acceleration.try_z()),
+        ] {
+            if let Some(axis) = axis {
+                fields.push(format!("{name}={axis}i"));
+            }
+        }
+        fields.push(format!("battery_voltage={}i", self.get_battery_voltage()));
+        fields.push(format!("tx_power={}i", self.get_tx_power()));
+        fields.push(format!("movement_counter={}i", self.movement_counter()));
+        fields.push(format!("measurement_number={}i", self.measurement_number()));
+
+        let mut line = format!(
+            "{},mac={} {}",
+            escape_influx(measurement, &[',', ' ']),
+            self.mac_as_str(),
+            fields.join(",")
+        );
+        if let Some(timestamp) = timestamp {
+            let nanos = timestamp
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since_epoch| since_epoch.as_nanos());
+            line.push_str(&format!(" {nanos}"));
+        }
+        line
+    }
+
+    /// Returns the sensor data in the Prometheus text exposition format.
+    ///
+    /// Every metric is labeled with the `mac` of the tag followed by `labels`, and preceded by
+    /// its `# HELP` and `# TYPE` lines:
+    ///
+    /// | Metric                          | Unit                     |
+    /// |---------------------------------|--------------------------|
+    /// | `ruuvi_temperature_celsius`     | °C                       |
+    /// | `ruuvi_humidity_percent`        | %                        |
+    /// | `ruuvi_pressure_pascals`        | Pa                       |
+    /// | `ruuvi_acceleration_g`          | G, labeled with `axis`   |
+    /// | `ruuvi_battery_volts`           | V                        |
+    /// | `ruuvi_tx_power_dbm`            | dBm                      |
+    /// | `ruuvi_movement_counter`        |                          |
+    /// | `ruuvi_measurement_number`      |                          |
+    ///
+    /// Fields the ruuvitag reported as not available are left out, so Prometheus doesn't ingest
+    /// the sentinel values as spikes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     // ruuvi_temperature_celsius{mac="CC:6F:70:EE:4C:AD",room="sauna"} 24.3
+    ///     *metrics.lock().unwrap() = current_sensor_data.to_prometheus(&[("room", "sauna")]);
+    /// }
+    /// ```
+    pub fn to_prometheus(&self, labels: &[(&str, &str)]) -> String {
+        self.to_prometheus_with_name(None, labels)
+    }
+
+    /// Returns the computed values of the sensor data in the Prometheus text exposition format
+    /// like `to_prometheus`, with the friendly `name` of the ruuvitag as the `name` label after
+    /// `mac`.
+    ///
+    /// The `name` label is left out if `name` is `None`. See `TagRegistry::to_prometheus` to
+    /// look the name up by mac address.
+    pub fn to_prometheus_with_name(&self, name: Option<&str>, labels: &[(&str, &str)]) -> String {
+        let mac = self.mac_as_str();
+        let mut common_labels = vec![("mac", mac.as_str())];
+        if let Some(name) = name {
+            common_labels.push(("name", name));
+        }
+        common_labels.extend_from_slice(labels);
+        let label_set = |extra: &[(&str, &str)]| {
+            let labels: Vec<String> = common_labels
+                .iter()
+                .chain(extra)
+                .map(|(name, value)| format!("{name}=\"{}\"", escape_prometheus(value)))
+                .collect();
+            labels.join(",")
+        };
+
+        let acceleration = self.get_acceleration_in_mg();
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, samples: &[(String, Option<f64>)]| {
+            if samples.iter().all(|(_, value)| value.is_none()) {
+                return;
+            }
+            out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} gauge\n"));
+            for (label_set, value) in samples {
+                if let Some(value) = value {
+                    out.push_str(&format!("{name}{{{label_set}}} {value}\n"));
+                }
+            }
+        };
+        gauge(
+            "ruuvi_temperature_celsius",
+            "Temperature measured by the ruuvitag.",
+            &[(label_set(&[]), self.try_temperature_in_celcius())],
+        );
+        gauge(
+            "ruuvi_humidity_percent",
+            "Relative humidity measured by the ruuvitag.",
+            &[(label_set(&[]), self.try_get_humidity())],
+        );
+        gauge(
+            "ruuvi_pressure_pascals",
+            "Air pressure measured by the ruuvitag.",
+            &[(label_set(&[]), self.try_get_pressure().map(f64::from))],
+        );
+        gauge(
+            "ruuvi_acceleration_g",
+            "Acceleration measured by the ruuvitag.",
+            &[
+                (
+                    label_set(&[("axis", "x")]),
+                    acceleration.try_x().map(milli_to_unit),
+                ),
+                (
+                    label_set(&[("axis", "y")]),
+                    acceleration.try_y().map(milli_to_unit),
+                ),
+                (
+                    label_set(&[("axis", "z")]),
+                    acceleration.try_z().map(milli_to_unit),
+                ),
+            ],
+        );
+        gauge(
+            "ruuvi_battery_volts",
+            "Battery voltage of the ruuvitag.",
+            &[(
+                label_set(&[]),
+                Some(milli_to_unit(self.get_battery_voltage())),
+            )],
+        );
+        gauge(
+            "ruuvi_tx_power_dbm",
+            "Transmit power of the ruuvitag.",
+            &[(label_set(&[]), Some(f64::from(self.get_tx_power())))],
+        );
+        gauge(
+            "ruuvi_movement_counter",
+            "Number of movements detected by the ruuvitag, wraps around at 255.",
+            &[(label_set(&[]), Some(f64::from(self.movement_counter())))],
+        );
+        gauge(
+            "ruuvi_measurement_number",
+            "Sequence number of the measurement, wraps around at 65535.",
+            &[(label_set(&[]), Some(f64::from(self.measurement_number())))],
+        );
+        out
+    }
+
+    /// Returns the temperature, humidity and pressure encoded as Cayenne Low Power Payload.
+    ///
+    /// The channels are stable:
+    ///
+    /// | Channel | Type                 | Resolution       |
+    /// |---------|----------------------|------------------|
+    /// | 1       | Temperature (`0x67`) | 0.1 °C signed    |
+    /// | 2       | Humidity (`0x68`)    | 0.5 % unsigned   |
+    /// | 3       | Barometer (`0x73`)   | 0.1 hPa unsigned |
+    ///
+    /// The channels of the values the ruuvitag reported as not available are left out, so the
+    /// payload of a reading without any valid values is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     lora_uplink.send(&current_sensor_data.to_cayenne_lpp())?;
+    /// }
+    /// ```
+    pub fn to_cayenne_lpp(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(11);
+        if let Some(temperature) = self.try_temperature_in_celcius() {
+            let temperature = (temperature * 10_f64).round() as i16;
+            payload.extend([LPP_CHANNEL_TEMPERATURE, LPP_TYPE_TEMPERATURE]);
+            payload.extend(temperature.to_be_bytes());
+        }
+        if let Some(humidity) = self.try_get_humidity() {
+            let humidity = (humidity * 2_f64).round() as u8;
+            payload.extend([LPP_CHANNEL_HUMIDITY, LPP_TYPE_HUMIDITY, humidity]);
+        }
+        if let Some(pressure) = self.try_get_pressure() {
+            let pressure = (pressure as f64 / 10_f64).round() as u16;
+            payload.extend([LPP_CHANNEL_PRESSURE, LPP_TYPE_BAROMETER]);
+            payload.extend(pressure.to_be_bytes());
+        }
+        payload
+    }
+
+    /// Returns the Cayenne Low Power Payload of `to_cayenne_lpp` followed by the acceleration.
+    ///
+    /// The acceleration is on channel 4 as Accelerometer (`0x71`) with 0.001 G signed resolution
+    /// per axis. The channel is left out if any of the axes is not available.
+    pub fn to_cayenne_lpp_with_acceleration(&self) -> Vec<u8> {
+        let acceleration = self.get_acceleration_in_mg();
+        let mut payload = self.to_cayenne_lpp();
+        if let (Some(x), Some(y), Some(z)) = (
+            acceleration.try_x(),
+            acceleration.try_y(),
+            acceleration.try_z(),
+        ) {
+            payload.extend([LPP_CHANNEL_ACCELERATION, LPP_TYPE_ACCELEROMETER]);
+            payload.extend(x.to_be_bytes());
+            payload.extend(y.to_be_bytes());
+            payload.extend(z.to_be_bytes());
+        }
+        payload
+    }
+}
+
+#[cfg(feature = "influxdb2")]
+impl SensorDataV5 {
+    /// Returns the sensor data as an InfluxDB v2 `DataPoint` of the given `measurement`.
+    ///
+    /// The mac address is stored as the `mac` tag and the computed values as fields. Like in
+    /// `to_influx_line`, the fields the ruuvitag reported as not available are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     let point = current_sensor_data.to_influx_point("ruuvi")?;
+    ///     client.write("bucket", futures::stream::iter(vec![point])).await?;
+    /// }
+    /// ```
+    pub fn to_influx_point(
+        &self,
+        measurement: &str,
+    ) -> Result<influxdb2::models::DataPoint, RuuviError> {
+        let acceleration = self.get_acceleration_in_mg();
+        let mut point =
+            influxdb2::models::DataPoint::builder(measurement).tag("mac", self.mac_as_str());
+        if let Some(temperature) = self.try_temperature_in_celcius() {
+            point = point.field("temperature", temperature);
+        }
+        if let Some(humidity) = self.try_get_humidity() {
+            point = point.field("humidity", humidity);
+        }
+        if let Some(pressure) = self.try_get_pressure() {
+            point = point.field("pressure", i64::from(pressure));
+        }
+        for (name, axis) in [
+            ("acceleration_x", acceleration.try_x()),
+            ("acceleration_y", acceleration.try_y()),
+            ("acceleration_z", acceleration.try_z()),
+        ] {
+            if let Some(axis) = axis {
+                point = point.field(name, i64::from(axis));
+            }
+        }
+        let point = point
+            .field("battery_voltage", i64::from(self.get_battery_voltage()))
+            .field("tx_power", i64::from(self.get_tx_power()))
+            .field("movement_counter", i64::from(self.movement_counter()))
+            .field("measurement_number", i64::from(self.measurement_number()))
+            .build()
+            .map_err(|e| RuuviError::Malformed(e.to_string()))?;
+        Ok(point)
+    }
+}
+
+/// Serialized form of `SensorDataV5` holding the computed values of the reading.
+///
+/// Fields the ruuvitag reported as not available are `None`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SensorDataRecord {
+    mac: String,
+    /// Temperature in °C.
+    temperature: Option<f64>,
+    /// Relative humidity in %.
+    humidity: Option<f64>,
+    /// Air pressure in Pa, like in `to_json`.
+    pressure: Option<u32>,
+    /// Acceleration in mG.
+    acceleration_x: Option<i16>,
+    acceleration_y: Option<i16>,
+    acceleration_z: Option<i16>,
+    /// Battery voltage in mV.
+    battery_voltage: u16,
+    /// Transmit power in dBm.
+    tx_power: i8,
+    movement_counter: u8,
+    measurement_number: u16,
+}
+
+#[cfg(feature = "serde")]
+impl From<&SensorDataV5> for SensorDataRecord {
+    fn from(sensor_data: &SensorDataV5) -> Self {
+        let acceleration = sensor_data.get_acceleration_in_mg();
+        Self {
+            mac: sensor_data.mac_as_str(),
+            temperature: sensor_data.try_temperature_in_celcius(),
+            humidity: sensor_data.try_get_humidity(),
+            pressure: sensor_data.try_get_pressure(),
+            acceleration_x: acceleration.try_x(),
+            acceleration_y: acceleration.try_y(),
+            acceleration_z: acceleration.try_z(),
+            battery_voltage: sensor_data.get_battery_voltage(),
+            tx_power: sensor_data.get_tx_power(),
+            movement_counter: sensor_data.movement_counter(),
+            measurement_number: sensor_data.measurement_number(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SensorDataRecord> for SensorDataV5 {
+    type Error = RuuviError;
+
+    fn try_from(record: SensorDataRecord) -> Result<Self, RuuviError> {
+        let out_of_range = |field: &str| RuuviError::Malformed(format!("{field} out of range"));
+        let temperature = match record.temperature {
+            Some(temperature) => i16::try_from((temperature * 200_f64).round() as i32)
+                .map_err(|_| out_of_range("temperature"))?,
+            None => INVALID_TEMPERATURE,
+        };
+        let humidity = match record.humidity {
+            Some(humidity) => u16::try_from((humidity * 400_f64).round() as i32)
+                .map_err(|_| out_of_range("humidity"))?,
+            None => INVALID_HUMIDITY,
+        };
+        let pressure = match record.pressure {
+            Some(pressure) => pressure
+                .checked_sub(50000)
+                .and_then(|pressure| u16::try_from(pressure).ok())
+                .ok_or_else(|| out_of_range("pressure"))?,
+            None => INVALID_PRESSURE,
+        };
+        let battery = record
+            .battery_voltage
+            .checked_sub(BATTERY_OFFSET)
+            .filter(|battery| *battery < 1 << 11)
+            .ok_or_else(|| out_of_range("battery_voltage"))?;
+        let tx_power = record.tx_power.wrapping_sub(TX_POWER_OFFSET);
+        if !(0..1 << 6).contains(&tx_power) || tx_power % 2 != 0 {
+            return Err(out_of_range("tx_power"));
+        }
+        let invalid_mac = || RuuviError::Malformed(format!("Invalid mac {:?}", record.mac));
+        let mut mac = [0_u8; 6];
+        let octets: Vec<&str> = record.mac.split(':').collect();
+        if octets.len() != mac.len() {
+            return Err(invalid_mac());
+        }
+        for (byte, octet) in mac.iter_mut().zip(octets) {
+            *byte = u8::from_str_radix(octet, 16).map_err(|_| invalid_mac())?;
+        }
+        Ok(SensorDataV5::new(
+            temperature,
+            humidity,
+            pressure,
+            Acceleration::new(
+                record.acceleration_x.unwrap_or(INVALID_ACCELERATION),
+                record.acceleration_y.unwrap_or(INVALID_ACCELERATION),
+                record.acceleration_z.unwrap_or(INVALID_ACCELERATION),
+            ),
+            (battery << 5) | (tx_power as u16 / 2),
+            record.movement_counter,
+            record.measurement_number,
+            mac,
+        ))
+    }
+}
+
+/// Serializes the computed values of the reading: temperature in °C, humidity in %, pressure in
+/// Pa, acceleration in mG, battery voltage in mV, transmit power in dBm and the mac address as
+/// a string. Fields reported as not available are serialized as `null`. The fields have the same
+/// names and units as in `to_json`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SensorDataV5 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SensorDataRecord::from(self).serialize(serializer)
+    }
+}
+
+/// Deserializes a reading serialized by the `Serialize` implementation.
+///
+/// The adapter and RSSI of the reading aren't serialized, so they are `None` after a round trip.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SensorDataV5 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SensorDataRecord::deserialize(deserializer)?
+            .try_into()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::ruuvitag::{SensorField, INVALID_ACCELERATION};
+    use crate::testing::PayloadBuilder;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            PayloadBuilder::spec_example().sensor_data().to_json(),
+            concat!(
+                "{\"mac\":\"CB:B8:33:4C:88:4F\",\"temperature\":24.3,\"humidity\":53.49,",
+                "\"pressure\":100044,\"acceleration_x\":4,\"acceleration_y\":-4,",
+                "\"acceleration_z\":1036,\"battery_voltage\":2977,\"tx_power\":4,",
+                "\"movement_counter\":66,\"measurement_number\":205,\"rssi\":null}"
+            )
+        );
+        let invalid = PayloadBuilder::new()
+            .not_available(SensorField::Temperature)
+            .not_available(SensorField::Humidity)
+            .not_available(SensorField::Pressure)
+            .acceleration(INVALID_ACCELERATION, 0, 0)
+            .sensor_data();
+        let json = invalid.to_json();
+        assert!(json.contains("\"temperature\":null,\"humidity\":null,\"pressure\":null"));
+        assert!(json.contains("\"acceleration_x\":null,\"acceleration_y\":0"));
+        assert!(json.ends_with("\"rssi\":null}"));
+    }
+
+    #[test]
+    fn test_to_json_with_name() {
+        let sensor_data = PayloadBuilder::spec_example().sensor_data();
+        let json = sensor_data.to_json_with_name(Some("Sauna \"1\"\n"));
+        assert!(json.starts_with(
+            "{\"mac\":\"CB:B8:33:4C:88:4F\",\"name\":\"Sauna \\\"1\\\"\\n\",\"temperature\":24.3,"
+        ));
+        assert_eq!(sensor_data.to_json_with_name(None), sensor_data.to_json());
+    }
+
+    #[test]
+    fn test_to_influx_line() {
+        let timestamp = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(
+            PayloadBuilder::spec_example()
+                .sensor_data()
+                .to_influx_line("ruuvi tags", Some(timestamp)),
+            concat!(
+                "ruuvi\\ tags,mac=CB:B8:33:4C:88:4F temperature=24.3,humidity=53.49,",
+                "pressure=100044i,acceleration_x=4i,acceleration_y=-4i,acceleration_z=1036i,",
+                "battery_voltage=2977i,tx_power=4i,movement_counter=66i,",
+                "measurement_number=205i 1700000000000000000"
+            )
+        );
+        let invalid = PayloadBuilder::new()
+            .not_available(SensorField::Temperature)
+            .sensor_data();
+        let line = invalid.to_influx_line("ruuvi", None);
+        assert!(line.starts_with("ruuvi,mac=00:00:00:00:00:00 humidity=0,"));
+        assert!(line.ends_with("measurement_number=0i"));
+    }
+
+    #[test]
+    fn test_to_prometheus() {
+        let metrics = PayloadBuilder::spec_example()
+            .sensor_data()
+            .to_prometheus(&[("room", "sauna \"1\"")]);
+        assert!(metrics.starts_with(concat!(
+            "# HELP ruuvi_temperature_celsius Temperature measured by the ruuvitag.\n",
+            "# TYPE ruuvi_temperature_celsius gauge\n",
+            "ruuvi_temperature_celsius{mac=\"CB:B8:33:4C:88:4F\",room=\"sauna \\\"1\\\"\"} 24.3\n"
+        )));
+        assert!(metrics.contains(
+            "ruuvi_acceleration_g{mac=\"CB:B8:33:4C:88:4F\",room=\"sauna \\\"1\\\"\",axis=\"z\"} 1.036\n"
+        ));
+        assert!(metrics.contains(
+            "ruuvi_battery_volts{mac=\"CB:B8:33:4C:88:4F\",room=\"sauna \\\"1\\\"\"} 2.977\n"
+        ));
+
+        let invalid = PayloadBuilder::new()
+            .not_available(SensorField::Temperature)
+            .sensor_data();
+        let metrics = invalid.to_prometheus(&[]);
+        assert!(!metrics.contains("ruuvi_temperature_celsius"));
+        assert!(metrics.contains("ruuvi_humidity_percent{mac=\"00:00:00:00:00:00\"} 0\n"));
+    }
+
+    #[test]
+    fn test_to_cayenne_lpp() {
+        assert_eq!(
+            PayloadBuilder::spec_example()
+                .sensor_data()
+                .to_cayenne_lpp(),
+            vec![0x01, 0x67, 0x00, 0xF3, 0x02, 0x68, 0x6B, 0x03, 0x73, 0x27, 0x14]
+        );
+
+        let humidity_only = PayloadBuilder::spec_example()
+            .not_available(SensorField::Temperature)
+            .not_available(SensorField::Pressure)
+            .sensor_data();
+        assert_eq!(humidity_only.to_cayenne_lpp(), vec![0x02, 0x68, 0x6B]);
+
+        let invalid = PayloadBuilder::spec_example()
+            .not_available(SensorField::Temperature)
+            .not_available(SensorField::Humidity)
+            .not_available(SensorField::Pressure)
+            .not_available(SensorField::Acceleration)
+            .sensor_data();
+        assert!(invalid.to_cayenne_lpp().is_empty());
+        assert!(invalid.to_cayenne_lpp_with_acceleration().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let sensor_data = PayloadBuilder::spec_example()
+            .acceleration(4, INVALID_ACCELERATION, 1036)
+            .sensor_data();
+        let json = serde_json::to_string(&sensor_data).unwrap();
+        assert!(json.contains("\"temperature\":24.3"));
+        assert!(json.contains("\"pressure\":100044"));
+        assert!(json.contains("\"acceleration_y\":null"));
+        assert!(json.contains("\"mac\":\"CB:B8:33:4C:88:4F\""));
+
+        let round_trip: crate::ruuvitag::SensorDataV5 = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_trip.to_raw_bytes(), sensor_data.to_raw_bytes());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_agrees_with_to_json() {
+        for sensor_data in [
+            PayloadBuilder::spec_example().sensor_data(),
+            PayloadBuilder::spec_example()
+                .not_available(SensorField::Pressure)
+                .sensor_data(),
+        ] {
+            let mut to_json: serde_json::Value =
+                serde_json::from_str(&sensor_data.to_json()).unwrap();
+            // The RSSI isn't serialized.
+            to_json.as_object_mut().unwrap().remove("rssi");
+            assert_eq!(serde_json::to_value(&sensor_data).unwrap(), to_json);
+        }
+    }
+
+    #[test]
+    fn test_to_cayenne_lpp_with_acceleration() {
+        let payload = PayloadBuilder::spec_example()
+            .sensor_data()
+            .to_cayenne_lpp_with_acceleration();
+        assert_eq!(
+            payload[11..],
+            [0x04, 0x71, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C]
+        );
+    }
+}
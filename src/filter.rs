@@ -0,0 +1,232 @@
+//! Module containing adaptors that filter or normalize ruuvitag readings.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::filter::coalesce_burst;
+//! use ruuviscanner::ruuvitag::subscribe_ruuvitag_timestamped;
+//! use std::time::Duration;
+//!
+//! // Replace with your mac address.
+//! let mut rx = subscribe_ruuvitag_timestamped("CC:6F:70:EE:4C:AD").await?;
+//! let mut readings = Vec::new();
+//! while readings.len() < 100 {
+//!     readings.push(rx.recv().await.unwrap());
+//! }
+//! for reading in coalesce_burst(readings, Duration::from_millis(100)) {
+//!     reading.data.print_sensor_data();
+//! }
+//! ```
+use crate::ruuvitag::{SensorDataV5, SensorField, TimestampedReading};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// Returns an iterator that coalesces bursts of repeated advertisements.
+///
+/// Ruuvitags repeat each measurement several times within ~100ms. The returned iterator emits
+/// the first reading of a burst and suppresses readings from the same tag with an identical
+/// measurement number that arrive within `window` of it. Genuine repeats of the same
+/// measurement number arriving later are passed through.
+///
+/// Arrival is the `received_at` time of the reading, not the time the iterator is polled, so a
+/// batch of readings collected earlier is coalesced the same as a live stream.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mut rx = subscribe_ruuvitag_timestamped("CC:6F:70:EE:4C:AD").await?;
+/// let mut readings = Vec::new();
+/// while readings.len() < 100 {
+///     readings.push(rx.recv().await.unwrap());
+/// }
+/// for reading in coalesce_burst(readings, Duration::from_millis(100)) {
+///     reading.data.print_sensor_data();
+/// }
+/// ```
+pub fn coalesce_burst<I>(readings: I, window: Duration) -> CoalesceBurst<I::IntoIter>
+where
+    I: IntoIterator<Item = TimestampedReading>,
+{
+    CoalesceBurst {
+        readings: readings.into_iter(),
+        window,
+        last_emitted: HashMap::new(),
+    }
+}
+
+/// Iterator returned by [`coalesce_burst`].
+#[derive(Debug)]
+pub struct CoalesceBurst<I> {
+    readings: I,
+    window: Duration,
+    /// Measurement number and arrival time of the last emitted reading per mac address.
+    last_emitted: HashMap<String, (u16, SystemTime)>,
+}
+
+impl<I> Iterator for CoalesceBurst<I>
+where
+    I: Iterator<Item = TimestampedReading>,
+{
+    type Item = TimestampedReading;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let reading = self.readings.next()?;
+            let mac = reading.data.mac_as_str();
+            let measurement_number = reading.data.measurement_number();
+            if let Some((emitted_number, emitted_at)) = self.last_emitted.get(&mac) {
+                // A reading received before the emitted one is within the window.
+                let elapsed = reading
+                    .received_at
+                    .duration_since(*emitted_at)
+                    .unwrap_or_default();
+                if *emitted_number == measurement_number && elapsed < self.window {
+                    continue;
+                }
+            }
+            self.last_emitted
+                .insert(mac, (measurement_number, reading.received_at));
+            return Some(reading);
+        }
+    }
+}
+
+/// Fills invalid fields of readings forward with the last valid value of the same tag.
+///
+/// Keeps plots continuous when a single field of a reading is reported as invalid. Filling is
+/// opt-in per field, other invalid fields are left as they are. The returned [`FilledReading`]
+/// tells which fields were filled so the interpolation can be shown honestly. A field is left
+/// invalid if the tag hasn't reported a valid value for it yet.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::filter::ForwardFill;
+/// use ruuviscanner::ruuvitag::SensorField;
+///
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut rx = subscribe_ruuvitag(mac).await?;
+/// let mut forward_fill = ForwardFill::new(&[SensorField::Temperature, SensorField::Humidity]);
+/// loop {
+///     let filled = forward_fill.fill(rx.recv().await.unwrap());
+///     println!("{} {:?}", filled.sensor_data.summary(), filled.filled);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ForwardFill {
+    fields: Vec<SensorField>,
+    /// Last reading per mac address with the filled fields holding their last valid value.
+    last_valid: HashMap<String, SensorDataV5>,
+}
+
+/// A reading returned by [`ForwardFill::fill`].
+#[derive(Debug, Clone)]
+pub struct FilledReading {
+    pub sensor_data: SensorDataV5,
+    /// Fields that were replaced with the last valid value.
+    pub filled: Vec<SensorField>,
+}
+
+impl ForwardFill {
+    /// Constructs a `ForwardFill` filling the given `fields`.
+    pub fn new(fields: &[SensorField]) -> Self {
+        Self {
+            fields: fields.to_vec(),
+            last_valid: HashMap::new(),
+        }
+    }
+
+    /// Fills the invalid fields of `sensor_data` with the last valid values of the tag.
+    pub fn fill(&mut self, mut sensor_data: SensorDataV5) -> FilledReading {
+        let mut filled = Vec::new();
+        let mac = sensor_data.mac_as_str();
+        if let Some(last_valid) = self.last_valid.get(&mac) {
+            for field in &self.fields {
+                if !sensor_data.is_valid(*field) && last_valid.is_valid(*field) {
+                    sensor_data.copy_field_from(*field, last_valid);
+                    filled.push(*field);
+                }
+            }
+        }
+        self.last_valid.insert(mac, sensor_data.clone());
+        FilledReading {
+            sensor_data,
+            filled,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::filter::{coalesce_burst, ForwardFill};
+    use crate::ruuvitag::{SensorField, TimestampedReading};
+    use crate::testing::PayloadBuilder;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_coalesce_burst_suppresses_repeats_within_window() {
+        // Measurements 1, 1, 2 and 2 received 40 ms apart.
+        let readings = [1, 1, 2, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(i, measurement_number)| TimestampedReading {
+                received_at: UNIX_EPOCH + Duration::from_millis(40) * i as u32,
+                data: PayloadBuilder::spec_example()
+                    .measurement_number(measurement_number)
+                    .sensor_data(),
+            });
+        let measurement_numbers: Vec<u16> = coalesce_burst(readings, Duration::from_millis(100))
+            .map(|x| x.data.measurement_number())
+            .collect();
+        assert_eq!(measurement_numbers, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_coalesce_burst_passes_repeats_outside_window() {
+        // The same measurement received 200 ms apart, but polled at once.
+        let reading = |received_at| TimestampedReading {
+            received_at,
+            data: PayloadBuilder::spec_example().sensor_data(),
+        };
+        let readings = vec![
+            reading(UNIX_EPOCH),
+            reading(UNIX_EPOCH + Duration::from_millis(200)),
+        ];
+        assert_eq!(
+            coalesce_burst(readings, Duration::from_millis(100)).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_forward_fill() {
+        let invalid_temperature =
+            PayloadBuilder::spec_example().not_available(SensorField::Temperature);
+        let mut forward_fill = ForwardFill::new(&[SensorField::Temperature]);
+
+        let filled = forward_fill.fill(invalid_temperature.sensor_data());
+        assert!(filled.filled.is_empty());
+        assert!(!filled.sensor_data.temperature_is_valid());
+
+        forward_fill.fill(
+            PayloadBuilder::spec_example()
+                .temperature(20.0)
+                .sensor_data(),
+        );
+        let filled = forward_fill.fill(
+            invalid_temperature
+                .clone()
+                .not_available(SensorField::Humidity)
+                .sensor_data(),
+        );
+        assert_eq!(filled.filled, vec![SensorField::Temperature]);
+        assert_eq!(filled.sensor_data.temperature_in_celcius(), 20.0);
+        // Humidity is not filled.
+        assert!(!filled.sensor_data.humidity_is_valid());
+
+        let filled = forward_fill.fill(invalid_temperature.sensor_data());
+        assert_eq!(filled.sensor_data.temperature_in_celcius(), 20.0);
+    }
+}
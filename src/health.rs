@@ -0,0 +1,410 @@
+//! Module to assist in monitoring the health of ruuvitags.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::health::FaultMonitor;
+//! use ruuviscanner::ruuvitag::subscribe_ruuvitag;
+//!
+//! // Replace with your mac address.
+//! let mac = "CC:6F:70:EE:4C:AD";
+//! let mut rx = subscribe_ruuvitag(mac).await?;
+//! let mut monitor = FaultMonitor::new();
+//! monitor.register(mac, 10, |mac, field| println!("{mac}: {field:?} sensor fault"));
+//! loop {
+//!     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+//!     monitor.observe(&current_sensor_data);
+//! }
+//! ```
+use crate::ruuvitag::{SensorDataV5, SensorField, DEFAULT_CHANNEL_CAPACITY};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc::{channel, Receiver};
+
+/// Temperature (°C) below which the voltage of a CR2477 battery sags noticeably.
+const COLD_TEMPERATURE_CELSIUS: f64 = 0.0;
+
+const MONITORED_FIELDS: [SensorField; 4] = [
+    SensorField::Temperature,
+    SensorField::Humidity,
+    SensorField::Pressure,
+    SensorField::Acceleration,
+];
+
+/// Callback invoked with the mac address and the faulty field of a ruuvitag.
+type FaultCallback = Box<dyn FnMut(&str, SensorField) + Send>;
+
+/// Detects sensor faults from consecutive invalid readings.
+///
+/// A ruuvitag reporting the invalid value of a field persistently often means that the sensor
+/// is faulty. The monitor counts consecutive invalid readings per field for each registered tag
+/// and invokes the tag's callback once the count reaches the registered threshold. The callback
+/// fires again only after the field has reported a valid value in between.
+///
+/// Tags without a registered callback are not monitored.
+#[derive(Default)]
+pub struct FaultMonitor {
+    tags: HashMap<String, MonitoredTag>,
+}
+
+struct MonitoredTag {
+    threshold: u32,
+    on_fault: FaultCallback,
+    consecutive_invalid: HashMap<SensorField, u32>,
+}
+
+impl FaultMonitor {
+    /// Constructs a `FaultMonitor` without any registered tags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `on_fault` to be called after `threshold` consecutive invalid readings of a
+    /// field from the ruuvitag with the given `mac_address`.
+    ///
+    /// Replaces the previously registered callback of the tag.
+    pub fn register<F>(&mut self, mac_address: &str, threshold: u32, on_fault: F)
+    where
+        F: FnMut(&str, SensorField) + Send + 'static,
+    {
+        self.tags.insert(
+            mac_address.to_uppercase(),
+            MonitoredTag {
+                threshold,
+                on_fault: Box::new(on_fault),
+                consecutive_invalid: HashMap::new(),
+            },
+        );
+    }
+
+    /// Feeds a reading to the monitor, invoking the tag's callback on detected faults.
+    pub fn observe(&mut self, sensor_data: &SensorDataV5) {
+        let mac = sensor_data.mac_as_str();
+        let tag = match self.tags.get_mut(&mac) {
+            Some(tag) => tag,
+            None => return,
+        };
+        for field in MONITORED_FIELDS {
+            let count = tag.consecutive_invalid.entry(field).or_insert(0);
+            if sensor_data.is_valid(field) {
+                *count = 0;
+                continue;
+            }
+            *count += 1;
+            if *count == tag.threshold {
+                (tag.on_fault)(&mac, field);
+            }
+        }
+    }
+}
+
+/// Tracks the battery voltage of a ruuvitag together with the temperature.
+///
+/// CR2477 voltage sags in the cold and recovers when it gets warmer, so a single low reading
+/// doesn't mean the battery is depleted. The tracker keeps the last `window` readings and
+/// compares low voltages against the readings taken at or above 0 °C:
+///
+/// - The battery is truly low when the latest reading taken at or above 0 °C is below the
+///   threshold.
+/// - The battery is sagging from the cold when the latest reading is below the threshold, it was
+///   taken below 0 °C and the battery isn't truly low. This is also the case when all of the
+///   readings in the window are cold, as there is nothing to compare against.
+///
+/// Readings with an invalid temperature are ignored.
+#[derive(Debug, Clone)]
+pub struct BatteryHealth {
+    low_threshold_mv: u16,
+    window: usize,
+    /// Temperature (°C) and battery voltage (mV) of the latest readings.
+    samples: VecDeque<(f64, u16)>,
+}
+
+impl BatteryHealth {
+    /// Constructs a `BatteryHealth` considering voltages below `low_threshold_mv` low and
+    /// keeping the last `window` readings.
+    pub fn new(low_threshold_mv: u16, window: usize) -> Self {
+        Self {
+            low_threshold_mv,
+            window,
+            samples: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Feeds a reading of the tracked ruuvitag.
+    pub fn push(&mut self, sensor_data: &SensorDataV5) {
+        if !sensor_data.is_valid(SensorField::Temperature) || self.window == 0 {
+            return;
+        }
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((
+            sensor_data.temperature_in_celcius(),
+            sensor_data.get_battery_voltage(),
+        ));
+    }
+
+    /// Returns `true` if the battery voltage is low even when it is not cold.
+    pub fn is_truly_low(&self) -> bool {
+        self.samples
+            .iter()
+            .rev()
+            .find(|(temperature, _)| *temperature >= COLD_TEMPERATURE_CELSIUS)
+            .is_some_and(|(_, battery_mv)| *battery_mv < self.low_threshold_mv)
+    }
+
+    /// Returns `true` if the battery voltage is low only because it is cold.
+    pub fn is_cold_sag(&self) -> bool {
+        match self.samples.back() {
+            Some((temperature, battery_mv)) => {
+                *battery_mv < self.low_threshold_mv
+                    && *temperature < COLD_TEMPERATURE_CELSIUS
+                    && !self.is_truly_low()
+            }
+            None => false,
+        }
+    }
+}
+
+/// Change of the liveness of a ruuvitag reported by `LivenessTracker`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LivenessEvent {
+    /// The ruuvitag with the mac address hasn't been seen within the timeout, e.g. because its
+    /// battery died or it was moved out of range.
+    TagWentStale(String),
+    /// The stale ruuvitag with the mac address was seen again.
+    TagRecovered(String),
+}
+
+/// Tracks when ruuvitags were last seen and detects the ones that have gone stale.
+///
+/// A tag is stale once it hasn't been seen for longer than the timeout. Only tags that have been
+/// seen at least once are tracked.
+#[derive(Debug, Clone)]
+pub struct LivenessTracker {
+    timeout: Duration,
+    /// Time each tag was last seen and whether it has been reported stale.
+    tags: HashMap<String, (SystemTime, bool)>,
+}
+
+impl LivenessTracker {
+    /// Constructs a `LivenessTracker` considering tags stale after `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            tags: HashMap::new(),
+        }
+    }
+
+    /// Records that the ruuvitag `mac_address` was seen at `seen_at`.
+    ///
+    /// Returns `LivenessEvent::TagRecovered` if the tag had been reported stale.
+    pub fn observe(&mut self, mac_address: &str, seen_at: SystemTime) -> Option<LivenessEvent> {
+        let mac_address = mac_address.to_uppercase();
+        let was_stale = match self.tags.get(&mac_address) {
+            Some((_, stale)) => *stale,
+            None => false,
+        };
+        self.tags.insert(mac_address.clone(), (seen_at, false));
+        was_stale.then_some(LivenessEvent::TagRecovered(mac_address))
+    }
+
+    /// Returns the time the ruuvitag `mac_address` was last seen.
+    pub fn last_seen(&self, mac_address: &str) -> Option<SystemTime> {
+        self.tags
+            .get(&mac_address.to_uppercase())
+            .map(|(last_seen, _)| *last_seen)
+    }
+
+    /// Returns the mac addresses of the tags that haven't been seen within the timeout at `now`.
+    pub fn stale_tags(&self, now: SystemTime) -> Vec<&str> {
+        self.tags
+            .iter()
+            .filter(|(_, (last_seen, _))| is_stale(self.timeout, *last_seen, now))
+            .map(|(mac_address, _)| mac_address.as_str())
+            .collect()
+    }
+
+    /// Returns `LivenessEvent::TagWentStale` of the tags that have gone stale at `now` since
+    /// the previous check.
+    pub fn check(&mut self, now: SystemTime) -> Vec<LivenessEvent> {
+        let mut events = Vec::new();
+        for (mac_address, (last_seen, stale)) in self.tags.iter_mut() {
+            if !*stale && is_stale(self.timeout, *last_seen, now) {
+                *stale = true;
+                events.push(LivenessEvent::TagWentStale(mac_address.clone()));
+            }
+        }
+        events
+    }
+}
+
+/// Returns `true` if more than `timeout` has passed from `last_seen` to `now`.
+fn is_stale(timeout: Duration, last_seen: SystemTime, now: SystemTime) -> bool {
+    now.duration_since(last_seen)
+        .is_ok_and(|elapsed| elapsed > timeout)
+}
+
+/// Watches the liveness of the ruuvitags of `readings`, e.g. of `subscribe_ruuvitags`.
+///
+/// Returns the readings passed through and a channel of the `LivenessEvent`s of the tags, as
+/// detected by a `LivenessTracker` with `timeout`. A tag going stale is reported within a
+/// quarter of `timeout` of it. The background task runs until `readings` ends or both of the
+/// returned receivers are dropped. Both receivers have to be received from or dropped, as a full
+/// channel holds back the other one.
+///
+/// # Panics
+///
+/// If `timeout` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::health::{watch_liveness, LivenessEvent};
+/// use std::time::Duration;
+///
+/// // Replace with your mac addresses.
+/// let macs = ["C0:CB:4E:3D:3E:12", "CC:6F:70:EE:4C:AD"];
+/// let rx = subscribe_ruuvitags(&macs).await?;
+/// let (mut rx, mut events) = watch_liveness(rx, Duration::from_secs(60));
+/// tokio::spawn(async move {
+///     while let Some(event) = events.recv().await {
+///         if let LivenessEvent::TagWentStale(mac) = event {
+///             println!("{mac} is offline");
+///         }
+///     }
+/// });
+/// while let Some((mac, current_sensor_data)) = rx.recv().await {
+///     println!("{mac}: {}", current_sensor_data.summary());
+/// }
+/// ```
+pub fn watch_liveness(
+    mut readings: Receiver<(String, SensorDataV5)>,
+    timeout: Duration,
+) -> (Receiver<(String, SensorDataV5)>, Receiver<LivenessEvent>) {
+    assert!(!timeout.is_zero(), "timeout must be non-zero");
+    let (readings_tx, readings_rx) = channel(DEFAULT_CHANNEL_CAPACITY);
+    let (events_tx, events_rx) = channel(DEFAULT_CHANNEL_CAPACITY);
+    let mut tracker = LivenessTracker::new(timeout);
+    let mut checks = tokio::time::interval((timeout / 4).max(Duration::from_millis(1)));
+    tokio::spawn(async move {
+        loop {
+            let events = tokio::select! {
+                reading = readings.recv() => {
+                    let Some((mac_address, sensor_data)) = reading else {
+                        break;
+                    };
+                    let event = tracker.observe(&mac_address, SystemTime::now());
+                    let _ = readings_tx.send((mac_address, sensor_data)).await;
+                    event.into_iter().collect()
+                }
+                _ = checks.tick() => tracker.check(SystemTime::now()),
+            };
+            for event in events {
+                let _ = events_tx.send(event).await;
+            }
+            if readings_tx.is_closed() && events_tx.is_closed() {
+                break;
+            }
+        }
+    });
+    (readings_rx, events_rx)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::health::{BatteryHealth, FaultMonitor, LivenessEvent, LivenessTracker};
+    use crate::ruuvitag::SensorField;
+    use crate::testing::PayloadBuilder;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_fault_monitor_fires_after_consecutive_invalid_readings() {
+        let faults = Arc::new(Mutex::new(Vec::new()));
+        let faults_clone = Arc::clone(&faults);
+        let mut monitor = FaultMonitor::new();
+        monitor.register("cb:b8:33:4c:88:4f", 2, move |mac, field| {
+            faults_clone.lock().unwrap().push((mac.to_string(), field));
+        });
+
+        let valid = PayloadBuilder::spec_example().sensor_data();
+        let invalid = PayloadBuilder::spec_example()
+            .not_available(SensorField::Temperature)
+            .sensor_data();
+        monitor.observe(&invalid);
+        monitor.observe(&valid);
+        monitor.observe(&invalid);
+        assert!(faults.lock().unwrap().is_empty());
+        monitor.observe(&invalid);
+        monitor.observe(&invalid);
+        assert_eq!(
+            *faults.lock().unwrap(),
+            vec![("CB:B8:33:4C:88:4F".to_string(), SensorField::Temperature)]
+        );
+    }
+
+    #[test]
+    fn test_battery_health_cold_sag() {
+        let mut battery = BatteryHealth::new(2500, 10);
+        // 20 °C and 2900 mV, then -20 °C and 2400 mV.
+        battery.push(
+            &PayloadBuilder::spec_example()
+                .temperature(20.0)
+                .battery_voltage(2900)
+                .sensor_data(),
+        );
+        battery.push(
+            &PayloadBuilder::spec_example()
+                .temperature(-20.0)
+                .battery_voltage(2400)
+                .sensor_data(),
+        );
+        assert!(battery.is_cold_sag());
+        assert!(!battery.is_truly_low());
+    }
+
+    #[test]
+    fn test_battery_health_truly_low() {
+        let mut battery = BatteryHealth::new(2500, 10);
+        battery.push(
+            &PayloadBuilder::spec_example()
+                .temperature(20.0)
+                .battery_voltage(2400)
+                .sensor_data(),
+        );
+        battery.push(
+            &PayloadBuilder::spec_example()
+                .temperature(-20.0)
+                .battery_voltage(2300)
+                .sensor_data(),
+        );
+        assert!(battery.is_truly_low());
+        assert!(!battery.is_cold_sag());
+    }
+
+    #[test]
+    fn test_liveness_tracker_reports_stale_tags_once() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut tracker = LivenessTracker::new(Duration::from_secs(60));
+        assert_eq!(tracker.observe("cc:6f:70:ee:4c:ad", start), None);
+        assert_eq!(tracker.observe("C0:CB:4E:3D:3E:12", start), None);
+        assert!(tracker.check(start + Duration::from_secs(60)).is_empty());
+
+        let later = start + Duration::from_secs(90);
+        assert_eq!(tracker.observe("C0:CB:4E:3D:3E:12", later), None);
+        assert_eq!(
+            tracker.check(later),
+            vec![LivenessEvent::TagWentStale("CC:6F:70:EE:4C:AD".to_string())]
+        );
+        assert!(tracker.check(later).is_empty());
+        assert_eq!(tracker.stale_tags(later), vec!["CC:6F:70:EE:4C:AD"]);
+
+        assert_eq!(
+            tracker.observe("CC:6F:70:EE:4C:AD", later),
+            Some(LivenessEvent::TagRecovered("CC:6F:70:EE:4C:AD".to_string()))
+        );
+        assert!(tracker.stale_tags(later).is_empty());
+        assert_eq!(tracker.last_seen("cc:6f:70:ee:4c:ad"), Some(later));
+    }
+}
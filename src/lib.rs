@@ -5,12 +5,11 @@
 //! ## Examples
 //!
 //! ```rust
-//! use ruuviscanner::ruuvitag::{subscribe_ruuvitag, SensorDataV5};
+//! use ruuviscanner::ruuvitag::subscribe_ruuvitag;
 //!
 //! let mac = "<mac address of you ruuvitag>";
-//! let rx = subscribe_ruuvitag(&mac).await?;
-//! loop {
-//!     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
+//! let mut rx = subscribe_ruuvitag(mac).await?;
+//! while let Some(current_sensor_data) = rx.recv().await {
 //!     current_sensor_data.print_sensor_data();
 //!     println!("{}", current_sensor_data.temperature_in_celcius());
 //!     println!("{}", current_sensor_data.get_humidity());
@@ -21,5 +20,24 @@
 //!     println!("{}", current_sensor_data.mac_as_str());
 //! }
 //! ```
+pub mod alert;
 pub mod bluetooth;
+pub mod capture;
+pub mod error;
+pub mod export;
+pub mod filter;
+pub mod health;
+pub mod mac;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod registry;
 pub mod ruuvitag;
+pub mod stats;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "websocket")]
+pub mod websocket;
@@ -0,0 +1,171 @@
+//! Module containing the mac address type of ruuvitags.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::mac::MacAddress;
+//!
+//! // Replace with your mac address.
+//! let mac: MacAddress = "cc-6f-70-ee-4c-ad".parse()?;
+//! assert_eq!(mac.to_string(), "CC:6F:70:EE:4C:AD");
+//! assert_eq!(mac.device_path("hci0"), "/org/bluez/hci0/dev_CC_6F_70_EE_4C_AD");
+//! ```
+use crate::bluetooth::adapter_path;
+use crate::error::RuuviError;
+use std::convert::{Infallible, TryFrom};
+use std::fmt;
+use std::str::FromStr;
+
+/// Mac address of a bluetooth device.
+///
+/// Parsed from six hex octets separated by colons or dashes, in either case, and displayed in
+/// the canonical uppercase colon-separated form, e.g. `CC:6F:70:EE:4C:AD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MacAddress([u8; 6]);
+
+impl MacAddress {
+    /// Constructs a `MacAddress` from its octets.
+    pub fn new(octets: [u8; 6]) -> Self {
+        Self(octets)
+    }
+
+    /// Returns the octets of the mac address.
+    pub fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+
+    /// Returns the bluez D-Bus path of the device seen by the bluetooth adapter `adapter`, e.g.
+    /// `/org/bluez/hci0/dev_CC_6F_70_EE_4C_AD`.
+    pub fn device_path(&self, adapter: &str) -> String {
+        let [a, b, c, d, e, f] = self.0;
+        format!(
+            "{}/dev_{a:02X}_{b:02X}_{c:02X}_{d:02X}_{e:02X}_{f:02X}",
+            adapter_path(adapter)
+        )
+    }
+}
+
+impl From<[u8; 6]> for MacAddress {
+    fn from(octets: [u8; 6]) -> Self {
+        Self(octets)
+    }
+}
+
+impl FromStr for MacAddress {
+    type Err = RuuviError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || RuuviError::InvalidMac(s.to_string());
+        let separator = if s.contains('-') { '-' } else { ':' };
+        let mut octets = [0u8; 6];
+        let mut parts = s.split(separator);
+        for octet in octets.iter_mut() {
+            let part = parts.next().ok_or_else(malformed)?;
+            if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(malformed());
+            }
+            *octet = u8::from_str_radix(part, 16).map_err(|_| malformed())?;
+        }
+        if parts.next().is_some() {
+            return Err(malformed());
+        }
+        Ok(Self(octets))
+    }
+}
+
+impl TryFrom<&str> for MacAddress {
+    type Error = RuuviError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for MacAddress {
+    type Error = RuuviError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<&String> for MacAddress {
+    type Error = RuuviError;
+
+    fn try_from(s: &String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_mac(&self.0, f)
+    }
+}
+
+/// Writes `octets` to `f` in the canonical uppercase colon-separated form without allocating.
+pub(crate) fn write_mac(octets: &[u8; 6], f: &mut impl fmt::Write) -> fmt::Result {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    for (i, octet) in octets.iter().enumerate() {
+        if i > 0 {
+            f.write_char(':')?;
+        }
+        f.write_char(char::from(HEX_DIGITS[usize::from(octet >> 4)]))?;
+        f.write_char(char::from(HEX_DIGITS[usize::from(octet & 0x0F)]))?;
+    }
+    Ok(())
+}
+
+impl From<Infallible> for RuuviError {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::error::RuuviError;
+    use crate::mac::{write_mac, MacAddress};
+
+    #[test]
+    fn test_mac_address_parse() {
+        let expected = MacAddress::new([0xCC, 0x6F, 0x70, 0xEE, 0x4C, 0xAD]);
+        assert_eq!("CC:6F:70:EE:4C:AD".parse::<MacAddress>().unwrap(), expected);
+        assert_eq!("cc-6f-70-ee-4c-ad".parse::<MacAddress>().unwrap(), expected);
+        assert_eq!(expected.to_string(), "CC:6F:70:EE:4C:AD");
+        assert_eq!(
+            expected.device_path("hci1"),
+            "/org/bluez/hci1/dev_CC_6F_70_EE_4C_AD"
+        );
+    }
+
+    #[test]
+    fn test_mac_address_write_mac() {
+        let mut mac = String::new();
+        write_mac(&[0x00, 0x0A, 0xF0, 0x9B, 0x4C, 0xFF], &mut mac).unwrap();
+        assert_eq!(mac, "00:0A:F0:9B:4C:FF");
+        assert_eq!(
+            MacAddress::new([0x00, 0x0A, 0xF0, 0x9B, 0x4C, 0xFF]).to_string(),
+            mac
+        );
+    }
+
+    #[test]
+    fn test_mac_address_parse_malformed() {
+        for mac in [
+            "",
+            "not-a-mac",
+            "CC:6F:70:EE:4C",
+            "CC:6F:70:EE:4C:AD:01",
+            "CC:6F:70-EE:4C:AD",
+            "CC:6F:70:EE:4C:GG",
+            "C:6F:70:EE:4C:ADD",
+            "CC:6F:70:EE:4C:+A",
+        ] {
+            assert!(
+                matches!(mac.parse::<MacAddress>(), Err(RuuviError::InvalidMac(invalid)) if invalid == mac),
+                "{mac}"
+            );
+        }
+    }
+}
@@ -1,25 +1,36 @@
-use ruuviscanner::ruuvitag::{subscribe_ruuvitag, SensorDataV5};
-use std::error::Error;
+use ruuviscanner::error::RuuviError;
+use ruuviscanner::ruuvitag::subscribe_ruuvitags;
+use std::io::{self, BufWriter, Write};
 
 #[tokio::main]
-async fn main() -> Result<(), Box<(dyn Error + 'static)>> {
+async fn main() -> Result<(), RuuviError> {
     // All of the ruuvitags I have.
-    // let _ruuvitags = vec![
-    //     "C0:CB:4E:3D:3E:12".to_owned(),
-    //     "E1:16:22:5D:F6:C9".to_owned(),
-    //     "CC:6F:70:EE:4C:AD".to_owned(),
-    // ];
-    let mac = "CC:6F:70:EE:4C:AD";
-    let rx = subscribe_ruuvitag(&mac).await?;
-    loop {
-        let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-        current_sensor_data.print_sensor_data();
-        println!("{}", current_sensor_data.temperature_in_celcius());
-        println!("{}", current_sensor_data.get_humidity());
-        println!("{}", current_sensor_data.get_pressure());
-        println!("{:?}", current_sensor_data.get_acceleration_in_mg());
-        println!("{}", current_sensor_data.get_battery_voltage());
-        println!("{}", current_sensor_data.get_tx_power());
-        println!("{}", current_sensor_data.mac_as_str());
+    let ruuvitags = [
+        "C0:CB:4E:3D:3E:12",
+        "E1:16:22:5D:F6:C9",
+        "CC:6F:70:EE:4C:AD",
+    ];
+    // Readings are flushed as soon as they are written so that consumers reading the output
+    // through a pipe get them in real time. `--no-flush` buffers the output for throughput.
+    let flush = !std::env::args().any(|arg| arg == "--no-flush");
+    let mut out: Box<dyn Write> = if flush {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(BufWriter::new(io::stdout().lock()))
+    };
+    let mut rx = subscribe_ruuvitags(&ruuvitags).await?;
+    while let Some((_mac, current_sensor_data)) = rx.recv().await {
+        current_sensor_data.write_sensor_data(&mut out)?;
+        writeln!(out, "{}", current_sensor_data.temperature_in_celcius())?;
+        writeln!(out, "{}", current_sensor_data.get_humidity())?;
+        writeln!(out, "{}", current_sensor_data.get_pressure())?;
+        writeln!(out, "{:?}", current_sensor_data.get_acceleration_in_mg())?;
+        writeln!(out, "{}", current_sensor_data.get_battery_voltage())?;
+        writeln!(out, "{}", current_sensor_data.get_tx_power())?;
+        writeln!(out, "{}", current_sensor_data.mac_as_str())?;
+        if flush {
+            out.flush()?;
+        }
     }
+    Ok(())
 }
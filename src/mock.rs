@@ -0,0 +1,82 @@
+//! Module to replay canned ruuvitag data without a ruuvitag or bluez.
+//!
+//! Available with the `mock` feature.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::capture::decode_dir;
+//! use ruuviscanner::mock::subscribe_mock;
+//! use std::time::Duration;
+//!
+//! let readings = decode_dir("captures")?.collect::<Result<Vec<_>, _>>()?;
+//! let mut rx = subscribe_mock(readings, Duration::from_secs(1));
+//! while let Some(current_sensor_data) = rx.recv().await {
+//!     current_sensor_data.print_sensor_data();
+//! }
+//! ```
+use crate::ruuvitag::{SensorDataV5, DEFAULT_CHANNEL_CAPACITY};
+use std::time::Duration;
+use tokio::sync::mpsc::{channel, Receiver};
+
+/// Returns a mpsc channel that sends the given `readings` one `interval` apart.
+///
+/// Replays the readings through the same channel type as `subscribe_ruuvitag`, so code
+/// consuming a subscription can be tested against the public API without a ruuvitag, bluez or
+/// D-Bus. The first reading is sent right away and the channel closes after the last one, like
+/// a subscription that stopped. Replaying stops early if the receiver is dropped.
+///
+/// # Panics
+///
+/// If called outside of a tokio runtime.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with readings of your ruuvitag, e.g. ones decoded with `capture::decode_dir`.
+/// let mut rx = subscribe_mock(readings, Duration::from_millis(10));
+/// let mut temperatures = Vec::new();
+/// while let Some(current_sensor_data) = rx.recv().await {
+///     temperatures.push(current_sensor_data.temperature_in_celcius());
+/// }
+/// assert_eq!(temperatures.len(), 5);
+/// ```
+pub fn subscribe_mock(readings: Vec<SensorDataV5>, interval: Duration) -> Receiver<SensorDataV5> {
+    let (tx, rx) = channel(DEFAULT_CHANNEL_CAPACITY);
+    tokio::spawn(async move {
+        for (i, reading) in readings.into_iter().enumerate() {
+            if i > 0 {
+                tokio::time::sleep(interval).await;
+            }
+            if tx.send(reading).await.is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::mock::subscribe_mock;
+    use crate::ruuvitag::SensorDataV5;
+    use crate::testing::PayloadBuilder;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_subscribe_mock_replays_readings() {
+        let readings: Vec<SensorDataV5> = (205..208)
+            .map(|measurement_number| {
+                PayloadBuilder::spec_example()
+                    .measurement_number(measurement_number)
+                    .sensor_data()
+            })
+            .collect();
+        let mut rx = subscribe_mock(readings.clone(), Duration::from_millis(1));
+        let mut replayed = Vec::new();
+        while let Some(reading) = rx.recv().await {
+            replayed.push(reading);
+        }
+        assert_eq!(replayed, readings);
+    }
+}
@@ -0,0 +1,137 @@
+//! Module to assist in publishing ruuvitag data to an MQTT broker, e.g. for Home Assistant.
+//!
+//! Available with the `mqtt` feature.
+//!
+//! Examples:
+//! ```
+//! use rumqttc::{AsyncClient, MqttOptions};
+//! use ruuviscanner::mqtt::publish_to_mqtt;
+//! use ruuviscanner::ruuvitag::subscribe_ruuvitag;
+//!
+//! let (client, mut eventloop) = AsyncClient::new(MqttOptions::new("ruuviscanner", "localhost", 1883), 10);
+//! tokio::spawn(async move { while eventloop.poll().await.is_ok() {} });
+//! // Replace with your mac address.
+//! let mac = "CC:6F:70:EE:4C:AD";
+//! let rx = subscribe_ruuvitag(mac).await?;
+//! publish_to_mqtt(rx, client, "ruuviscanner").await?;
+//! ```
+use crate::error::RuuviError;
+use crate::ruuvitag::SensorDataV5;
+use rumqttc::{AsyncClient, QoS};
+use std::collections::HashSet;
+use tokio::sync::mpsc::Receiver;
+
+/// Topic prefix Home Assistant listens to for MQTT discovery by default.
+pub const HOME_ASSISTANT_DISCOVERY_PREFIX: &str = "homeassistant";
+
+/// Sensors announced to Home Assistant: the key in the state JSON, the name, the unit and the
+/// device class.
+const DISCOVERED_SENSORS: [(&str, &str, &str, &str); 5] = [
+    ("temperature", "Temperature", "°C", "temperature"),
+    ("humidity", "Humidity", "%", "humidity"),
+    ("pressure", "Pressure", "Pa", "pressure"),
+    ("battery_voltage", "Battery voltage", "mV", "voltage"),
+    ("tx_power", "TX power", "dBm", "signal_strength"),
+];
+
+/// Publishes the readings of `receiver` to the MQTT broker of `client` until the subscription
+/// ends.
+///
+/// Every reading is published as the JSON of [`SensorDataV5::to_json`] to
+/// `<topic_prefix>/<mac>/state`. The first reading of each ruuvitag also publishes retained
+/// Home Assistant MQTT discovery configs under [`HOME_ASSISTANT_DISCOVERY_PREFIX`], so the
+/// temperature, humidity, pressure, battery voltage and transmit power sensors of the tag
+/// register automatically.
+///
+/// The event loop of `client` has to be polled for the messages to be sent. Returns an error if
+/// the event loop has stopped.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let rx = subscribe_ruuvitag(mac).await?;
+/// publish_to_mqtt(rx, client, "ruuviscanner").await?;
+/// ```
+pub async fn publish_to_mqtt(
+    mut receiver: Receiver<SensorDataV5>,
+    client: AsyncClient,
+    topic_prefix: &str,
+) -> Result<(), RuuviError> {
+    let mut discovered = HashSet::new();
+    while let Some(sensor_data) = receiver.recv().await {
+        let mac = sensor_data.mac_as_str();
+        let state_topic = format!("{topic_prefix}/{mac}/state");
+        if !discovered.contains(&mac) {
+            for (topic, config) in discovery_configs(&mac, &state_topic) {
+                client
+                    .publish(topic, QoS::AtLeastOnce, true, config)
+                    .await?;
+            }
+            discovered.insert(mac);
+        }
+        client
+            .publish(state_topic, QoS::AtMostOnce, false, sensor_data.to_json())
+            .await?;
+    }
+    Ok(())
+}
+
+/// Returns the topics and payloads of the Home Assistant discovery configs of a ruuvitag.
+fn discovery_configs(mac: &str, state_topic: &str) -> Vec<(String, String)> {
+    let object_id = format!("ruuvitag_{}", mac.replace(':', "").to_lowercase());
+    DISCOVERED_SENSORS
+        .iter()
+        .map(|(key, name, unit, device_class)| {
+            let topic =
+                format!("{HOME_ASSISTANT_DISCOVERY_PREFIX}/sensor/{object_id}/{key}/config");
+            let config = format!(
+                concat!(
+                    "{{\"name\":\"{name}\",\"unique_id\":\"{object_id}_{key}\",",
+                    "\"state_topic\":\"{state_topic}\",",
+                    "\"value_template\":\"{{{{ value_json.{key} }}}}\",",
+                    "\"unit_of_measurement\":\"{unit}\",\"device_class\":\"{device_class}\",",
+                    "\"state_class\":\"measurement\",",
+                    "\"device\":{{\"identifiers\":[\"{object_id}\"],\"name\":\"RuuviTag {mac}\",",
+                    "\"manufacturer\":\"Ruuvi Innovations\"}}}}"
+                ),
+                name = name,
+                object_id = object_id,
+                key = key,
+                state_topic = state_topic,
+                unit = unit,
+                device_class = device_class,
+                mac = mac,
+            );
+            (topic, config)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::mqtt::{discovery_configs, DISCOVERED_SENSORS};
+
+    #[test]
+    fn test_discovery_configs() {
+        let state_topic = "ruuviscanner/CB:B8:33:4C:88:4F/state";
+        let configs = discovery_configs("CB:B8:33:4C:88:4F", state_topic);
+        assert_eq!(configs.len(), DISCOVERED_SENSORS.len());
+        for ((topic, config), (key, ..)) in configs.iter().zip(DISCOVERED_SENSORS) {
+            assert_eq!(
+                *topic,
+                format!("homeassistant/sensor/ruuvitag_cbb8334c884f/{key}/config")
+            );
+            let config: serde_json::Value = serde_json::from_str(config).unwrap();
+            assert_eq!(config["unique_id"], format!("ruuvitag_cbb8334c884f_{key}"));
+            assert_eq!(config["state_topic"], state_topic);
+            assert_eq!(
+                config["value_template"],
+                format!("{{{{ value_json.{key} }}}}")
+            );
+            assert_eq!(config["device"]["identifiers"][0], "ruuvitag_cbb8334c884f");
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Module to assist in recording ruuvitag data as OpenTelemetry metrics.
+//!
+//! Available with the `otel` feature.
+//!
+//! Examples:
+//! ```
+//! use opentelemetry::global;
+//! use ruuviscanner::otel::OtelExporter;
+//! use ruuviscanner::ruuvitag::subscribe_ruuvitag;
+//!
+//! let exporter = OtelExporter::new(&global::meter("ruuviscanner"));
+//! // Replace with your mac address.
+//! let mac = "CC:6F:70:EE:4C:AD";
+//! let mut rx = subscribe_ruuvitag(mac).await?;
+//! loop {
+//!     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+//!     exporter.record(&current_sensor_data);
+//! }
+//! ```
+use crate::ruuvitag::SensorDataV5;
+use opentelemetry::metrics::{Gauge, Meter};
+use opentelemetry::KeyValue;
+
+/// Records ruuvitag readings as OpenTelemetry gauges labeled with the `mac` of the tag.
+///
+/// Records the gauges `ruuvitag.temperature` (°C), `ruuvitag.humidity` (%),
+/// `ruuvitag.pressure` (Pa) and `ruuvitag.battery_voltage` (mV). Fields reported as not available
+/// by the tag are not recorded. The metrics are exported by the meter provider of the given
+/// `Meter`, e.g. to an OTLP pipeline.
+#[derive(Debug, Clone)]
+pub struct OtelExporter {
+    temperature: Gauge<f64>,
+    humidity: Gauge<f64>,
+    pressure: Gauge<u64>,
+    battery_voltage: Gauge<u64>,
+}
+
+impl OtelExporter {
+    /// Constructs an `OtelExporter` creating its instruments with `meter`.
+    pub fn new(meter: &Meter) -> Self {
+        Self {
+            temperature: meter
+                .f64_gauge("ruuvitag.temperature")
+                .with_description("Temperature measured by the ruuvitag")
+                .with_unit("Cel")
+                .init(),
+            humidity: meter
+                .f64_gauge("ruuvitag.humidity")
+                .with_description("Relative humidity measured by the ruuvitag")
+                .with_unit("%")
+                .init(),
+            pressure: meter
+                .u64_gauge("ruuvitag.pressure")
+                .with_description("Air pressure measured by the ruuvitag")
+                .with_unit("Pa")
+                .init(),
+            battery_voltage: meter
+                .u64_gauge("ruuvitag.battery_voltage")
+                .with_description("Battery voltage of the ruuvitag")
+                .with_unit("mV")
+                .init(),
+        }
+    }
+
+    /// Records the values of `sensor_data` to the gauges.
+    pub fn record(&self, sensor_data: &SensorDataV5) {
+        let attributes = [KeyValue::new("mac", sensor_data.mac_as_str())];
+        if sensor_data.temperature_is_valid() {
+            self.temperature
+                .record(sensor_data.temperature_in_celcius(), &attributes);
+        }
+        if sensor_data.humidity_is_valid() {
+            self.humidity
+                .record(sensor_data.get_humidity(), &attributes);
+        }
+        if sensor_data.pressure_is_valid() {
+            self.pressure
+                .record(u64::from(sensor_data.get_pressure()), &attributes);
+        }
+        self.battery_voltage
+            .record(u64::from(sensor_data.get_battery_voltage()), &attributes);
+    }
+}
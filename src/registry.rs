@@ -0,0 +1,126 @@
+//! Module to give ruuvitags friendly names.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::registry::TagRegistry;
+//! use ruuviscanner::ruuvitag::subscribe_ruuvitags;
+//! use std::collections::HashMap;
+//!
+//! // Replace with your mac addresses.
+//! let registry = TagRegistry::new(HashMap::from([
+//!     ("C0:CB:4E:3D:3E:12".to_string(), "Sauna".to_string()),
+//!     ("CC:6F:70:EE:4C:AD".to_string(), "Fridge".to_string()),
+//! ]))?;
+//! let macs = registry.mac_addresses();
+//! let macs: Vec<&str> = macs.iter().map(String::as_str).collect();
+//! let mut rx = subscribe_ruuvitags(&macs).await?;
+//! while let Some((mac, current_sensor_data)) = rx.recv().await {
+//!     let name = registry.resolve(&current_sensor_data).unwrap_or(&mac);
+//!     println!("{name}: {}", registry.to_json(&current_sensor_data));
+//! }
+//! ```
+use crate::error::RuuviError;
+use crate::mac::MacAddress;
+use crate::ruuvitag::SensorDataV5;
+use std::collections::HashMap;
+
+/// Maps the mac addresses of ruuvitags to user-chosen names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagRegistry {
+    names: HashMap<MacAddress, String>,
+}
+
+impl TagRegistry {
+    /// Constructs a `TagRegistry` from a map of mac addresses to names.
+    ///
+    /// # Errors
+    ///
+    /// `RuuviError::InvalidMac` if a key of `names` isn't a mac address.
+    pub fn new(names: HashMap<String, String>) -> Result<Self, RuuviError> {
+        let names = names
+            .into_iter()
+            .map(|(mac, name)| Ok((mac.parse()?, name)))
+            .collect::<Result<_, RuuviError>>()?;
+        Ok(Self { names })
+    }
+
+    /// Names the ruuvitag with the given `mac_address`, replacing its previous name.
+    pub fn insert(&mut self, mac_address: MacAddress, name: impl Into<String>) {
+        self.names.insert(mac_address, name.into());
+    }
+
+    /// Returns the name of the ruuvitag with the given `mac_address`, if it has one.
+    pub fn name(&self, mac_address: &MacAddress) -> Option<&str> {
+        self.names.get(mac_address).map(String::as_str)
+    }
+
+    /// Returns the name of the ruuvitag that sent `sensor_data`, if it has one.
+    pub fn resolve(&self, sensor_data: &SensorDataV5) -> Option<&str> {
+        self.name(&sensor_data.mac())
+    }
+
+    /// Returns the mac addresses of the named ruuvitags in ascending order, e.g. to subscribe to
+    /// them with `subscribe_ruuvitags`.
+    pub fn mac_addresses(&self) -> Vec<String> {
+        let mut macs: Vec<&MacAddress> = self.names.keys().collect();
+        macs.sort();
+        macs.into_iter().map(MacAddress::to_string).collect()
+    }
+
+    /// Returns `sensor_data` as a JSON object with the name of the ruuvitag, if it has one.
+    ///
+    /// See `SensorDataV5::to_json_with_name`.
+    pub fn to_json(&self, sensor_data: &SensorDataV5) -> String {
+        sensor_data.to_json_with_name(self.resolve(sensor_data))
+    }
+
+    /// Returns `sensor_data` in the Prometheus text exposition format with the name of the
+    /// ruuvitag as the `name` label, if it has one.
+    ///
+    /// See `SensorDataV5::to_prometheus_with_name`.
+    pub fn to_prometheus(&self, sensor_data: &SensorDataV5, labels: &[(&str, &str)]) -> String {
+        sensor_data.to_prometheus_with_name(self.resolve(sensor_data), labels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::error::RuuviError;
+    use crate::registry::TagRegistry;
+    use crate::testing::PayloadBuilder;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_tag_registry_resolve() {
+        let mut registry = TagRegistry::new(HashMap::from([(
+            "cb-b8-33-4c-88-4f".to_string(),
+            "Sauna".to_string(),
+        )]))
+        .unwrap();
+        let sensor_data = |mac| PayloadBuilder::spec_example().mac(mac).sensor_data();
+        let sauna = sensor_data([0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F]);
+        let unnamed = sensor_data([0; 6]);
+        assert_eq!(registry.resolve(&sauna), Some("Sauna"));
+        assert_eq!(registry.resolve(&unnamed), None);
+        assert!(registry
+            .to_json(&sauna)
+            .starts_with("{\"mac\":\"CB:B8:33:4C:88:4F\",\"name\":\"Sauna\","));
+        assert!(registry
+            .to_prometheus(&sauna, &[])
+            .contains("ruuvi_battery_volts{mac=\"CB:B8:33:4C:88:4F\",name=\"Sauna\"} 2.977\n"));
+        assert_eq!(registry.to_json(&unnamed), unnamed.to_json());
+
+        registry.insert(unnamed.mac(), "Fridge");
+        assert_eq!(registry.resolve(&unnamed), Some("Fridge"));
+        assert_eq!(
+            registry.mac_addresses(),
+            ["00:00:00:00:00:00", "CB:B8:33:4C:88:4F"]
+        );
+
+        assert!(matches!(
+            TagRegistry::new(HashMap::from([("sauna".to_string(), "Sauna".to_string())])),
+            Err(RuuviError::InvalidMac(mac)) if mac == "sauna"
+        ));
+    }
+}
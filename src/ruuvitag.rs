@@ -5,24 +5,124 @@
 //! use ruuviscanner::ruuvitag::subscribe_ruuvitag;
 //! // Replace with your mac address.
 //! let mac = "CC:6F:70:EE:4C:AD";
-//! let rx = subscribe_ruuvitag(&mac).await?;
+//! let mut rx = subscribe_ruuvitag(mac).await?;
 //! loop {
-//!     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
+//!     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
 //!     current_sensor_data.print_sensor_data();
 //! }
 //! ```
-use crate::bluetooth::connect_bluetooth;
+use crate::bluetooth::{
+    adapter_path, connect_bluetooth_async, discover_ruuvitags_with_adapters, find_devices_by_name,
+    power_on_adapter_async, stop_discovery_async, ConnectOptions, DiscoveryFilter, DEFAULT_ADAPTER,
+};
+use crate::error::RuuviError;
+use crate::mac::{write_mac, MacAddress};
 use dbus::arg;
-use dbus::blocking::Connection;
-use dbus::nonblock::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged;
+use dbus::message::SignalArgs;
+use dbus::nonblock::stdintf::org_freedesktop_dbus::{
+    ObjectManagerInterfacesAdded, PropertiesPropertiesChanged,
+};
+use dbus::nonblock::{MsgMatch, SyncConnection};
 use dbus::Message;
+use futures_util::{Stream, StreamExt};
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
-use std::error::Error;
-use std::sync::mpsc::{channel, Receiver};
-use std::time::Duration;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::ops::{Add, Index, RangeInclusive, Sub};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_util::sync::CancellationToken;
 
-const BATTERY_OFFSET: u16 = 1600;
-const TX_POWER_OFFSET: i8 = -40;
+/// Offset of the battery voltage of data format 5 in mV.
+///
+/// The 11 most significant bits of the power info are the battery voltage above 1.6 V, see
+/// <https://github.com/ruuvi/ruuvi-sensor-protocols/blob/master/dataformat_05.md>.
+pub const BATTERY_OFFSET: u16 = 1600;
+/// Data format byte of ruuvi data format 5 (RAWv2).
+const DATA_FORMAT_V5: u8 = 0x05;
+/// Data format byte of ruuvi data format 3 (RAWv1).
+const DATA_FORMAT_V3: u8 = 0x03;
+/// Offset of the pressure of data formats 2, 3 and 4 in Pa.
+const PRESSURE_OFFSET: u32 = 50000;
+/// Bluetooth SIG company identifier of Ruuvi Innovations Ltd.
+const RUUVI_MANUFACTURER_ID: u16 = 0x0499;
+/// AD type of manufacturer specific data in bluetooth advertisements.
+const AD_TYPE_MANUFACTURER_DATA: u8 = 0xFF;
+/// UUID of the Eddystone service data carrying the URL of ruuvi data formats 2 and 4.
+const EDDYSTONE_SERVICE_UUID: &str = "0000feaa-0000-1000-8000-00805f9b34fb";
+/// Frame type byte of Eddystone-URL frames.
+const EDDYSTONE_URL_FRAME: u8 = 0x10;
+/// Data format bytes of ruuvi data formats 2 and 4 (URL).
+const DATA_FORMAT_V2: u8 = 0x02;
+const DATA_FORMAT_V4: u8 = 0x04;
+/// Offset of the TX power of data format 5 in dBm.
+///
+/// The 5 least significant bits of the power info are the TX power above -40 dBm in 2 dBm
+/// steps, see <https://github.com/ruuvi/ruuvi-sensor-protocols/blob/master/dataformat_05.md>.
+pub const TX_POWER_OFFSET: i8 = -40;
+/// Number of readings the channel of a subscription holds unless configured otherwise.
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 32;
+/// Number of readings each ruuvitag of a subscription to several ruuvitags can have waiting for
+/// its turn, see `subscribe_ruuvitags`.
+pub const TAG_CHANNEL_CAPACITY: usize = 4;
+/// Raw temperature (`0x8000`) the ruuvitag uses to report the temperature as not available.
+pub const INVALID_TEMPERATURE: i16 = i16::MIN;
+/// Raw humidity (`0xFFFF`) the ruuvitag uses to report the humidity as not available.
+pub const INVALID_HUMIDITY: u16 = u16::MAX;
+/// Raw pressure (`0xFFFF`) the ruuvitag uses to report the pressure as not available.
+pub const INVALID_PRESSURE: u16 = u16::MAX;
+/// Raw acceleration (`0x8000`) the ruuvitag uses to report an axis as not available.
+pub const INVALID_ACCELERATION: i16 = i16::MIN;
+/// Deviation from 1 G in mG within which an axis is considered to point up or down by
+/// [`Acceleration::orientation`].
+pub const ORIENTATION_TOLERANCE_MG: i16 = 250;
+/// Magnitude of the acceleration in mG below which [`Acceleration::is_free_fall`] considers
+/// the tag to be falling.
+pub const DEFAULT_FREE_FALL_THRESHOLD_MG: f64 = 300.0;
+/// Magnitude of the acceleration in mG above which [`Acceleration::is_impact`] considers the
+/// tag to have hit something.
+pub const DEFAULT_IMPACT_THRESHOLD_MG: f64 = 3000.0;
+/// Battery voltage in mV below which ruuvitags get unreliable, especially in the cold.
+pub const LOW_BATTERY_THRESHOLD_MV: u16 = 2500;
+/// Approximate discharge curve of a CR2477 battery as pairs of voltage (mV) and remaining
+/// capacity (%), in descending order of voltage.
+///
+/// The voltage of a lithium coin cell stays flat for most of its capacity and drops steeply
+/// when it is nearly empty.
+const BATTERY_DISCHARGE_CURVE: [(u16, u8); 7] = [
+    (3000, 100),
+    (2900, 85),
+    (2800, 60),
+    (2700, 35),
+    (2500, 15),
+    (2200, 5),
+    (2000, 0),
+];
+/// Air pressure of the standard atmosphere at sea level in Pa.
+pub const STANDARD_SEA_LEVEL_PRESSURE_PA: u32 = 101325;
+/// Pressure of one millimeter of mercury in Pa.
+const PA_PER_MMHG: f64 = 133.322387415;
+/// Pressure of one inch of mercury in Pa.
+const PA_PER_INHG: f64 = 3386.389;
+/// Magnus-Tetens approximation coefficients.
+const MAGNUS_P0: f64 = 611.2;
+const MAGNUS_A: f64 = 17.62;
+const MAGNUS_B: f64 = 243.12;
+/// Specific gas constant of water vapor in J/(kg·K).
+const WATER_VAPOR_GAS_CONSTANT: f64 = 461.5;
+/// Operating ranges of the ruuvitag sensors, readings outside them are implausible.
+const PLAUSIBLE_TEMPERATURE_CELSIUS: RangeInclusive<f64> = -40.0..=85.0;
+const PLAUSIBLE_HUMIDITY: RangeInclusive<f64> = 0.0..=100.0;
+const PLAUSIBLE_PRESSURE_PA: RangeInclusive<u32> = 30000..=110000;
 
 /// Joins two u8 primitives together.
 ///
@@ -31,366 +131,4865 @@ fn join_u8(left: u8, right: u8) -> u16 {
     (left as u16) << 8 | right as u16
 }
 
+/// Returns the temperature in millicelcius encoded as a sign bit, the integer part and the
+/// hundredths, as in ruuvi data formats 2, 3 and 4.
+fn sign_magnitude_millicelcius(integer: u8, fraction: u8) -> i32 {
+    let millicelcius = i32::from(integer & 0x7F) * 1000 + i32::from(fraction) * 10;
+    if integer & 0x80 == 0 {
+        millicelcius
+    } else {
+        -millicelcius
+    }
+}
+
+/// Decodes base64, accepting both the standard and the URL safe alphabet.
+pub(crate) fn decode_base64(base64: &str) -> Result<Vec<u8>, RuuviError> {
+    let mut bytes = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for character in base64.trim().trim_end_matches('=').bytes() {
+        let value = match character {
+            b'A'..=b'Z' => character - b'A',
+            b'a'..=b'z' => character - b'a' + 26,
+            b'0'..=b'9' => character - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => {
+                return Err(RuuviError::Malformed(format!(
+                    "Invalid base64 character {:?}",
+                    character as char
+                )))
+            }
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Returns the battery voltage in mV encoded in the `power_info` of data format 5.
+///
+/// ```
+/// use ruuviscanner::ruuvitag::decode_battery_voltage;
+///
+/// const BATTERY_MV: u16 = decode_battery_voltage(0xAC36);
+/// assert_eq!(BATTERY_MV, 2977);
+/// ```
+pub const fn decode_battery_voltage(power_info: u16) -> u16 {
+    (power_info >> 5) + BATTERY_OFFSET
+}
+
+/// Returns the TX power in dBm encoded in the `power_info` of data format 5.
+///
+/// ```
+/// use ruuviscanner::ruuvitag::decode_tx_power;
+///
+/// const TX_POWER_DBM: i8 = decode_tx_power(0xAC36);
+/// assert_eq!(TX_POWER_DBM, 4);
+/// ```
+pub const fn decode_tx_power(power_info: u16) -> i8 {
+    (power_info & 0x1f) as i8 * 2 + TX_POWER_OFFSET
+}
+
+/// Returns `None` for the acceleration axis sentinel `0x8000`.
+fn valid_axis(axis: i16) -> Option<i16> {
+    (axis != INVALID_ACCELERATION).then_some(axis)
+}
+
+/// Returns the saturation vapor pressure of water in Pa at the given temperature.
+///
+/// Uses the Magnus-Tetens approximation.
+fn saturation_vapor_pressure_pa(temperature_celsius: f64) -> f64 {
+    MAGNUS_P0 * (MAGNUS_A * temperature_celsius / (MAGNUS_B + temperature_celsius)).exp()
+}
+
+/// Returns the RSSI of dbus message `PropertiesChanged`, if present.
+fn rssi(changed_properties: &arg::PropMap) -> Option<i16> {
+    changed_properties
+        .get("RSSI")
+        .and_then(|rssi| rssi.0.as_i64())
+        .map(|rssi| rssi as i16)
+}
+
+/// Returns `true` if the bluez device `properties` contain Ruuvi manufacturer data.
+pub(crate) fn has_ruuvi_manufacturer_data(properties: &arg::PropMap) -> bool {
+    properties.contains_key("ManufacturerData") && ruuvi_manufacturer_data(properties).is_ok()
+}
+
+/// Length of the longest Ruuvi manufacturer data payload, the one of data format 5.
+const MAX_PAYLOAD_LENGTH: usize = 24;
+
+/// Ruuvi manufacturer data payload copied out of a D-Bus message without allocating.
+///
+/// Dereferences to the bytes of the payload.
+struct ManufacturerPayload {
+    bytes: [u8; MAX_PAYLOAD_LENGTH],
+    len: usize,
+}
+
+impl std::ops::Deref for ManufacturerPayload {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Returns the Ruuvi manufacturer data payload of dbus message `PropertiesChanged`.
+///
+/// Bluez also emits `PropertiesChanged` without `ManufacturerData`, e.g. when only the RSSI or
+/// the connection state changed. Those are reported as `RuuviError::MissingManufacturerData`
+/// without found manufacturers.
+fn ruuvi_manufacturer_data(
+    changed_properties: &arg::PropMap,
+) -> Result<ManufacturerPayload, RuuviError> {
+    let manufacturer_data = match changed_properties.get("ManufacturerData") {
+        Some(x) => x,
+        None => return Err(RuuviError::MissingManufacturerData { found: Vec::new() }),
+    };
+    let mut entries = match manufacturer_data.0.as_iter() {
+        Some(x) => x,
+        None => {
+            return Err(RuuviError::Malformed(
+                "ManufacturerData couldn't be collected".to_string(),
+            ))
+        }
+    };
+    // The dict is iterated as alternating keys and values. Other manufacturers' entries may
+    // be present too, so look up the Ruuvi entry by its key instead of its position.
+    let mut ruuvi_data = None;
+    let mut found = Vec::new();
+    while let Some(manufacturer_key) = entries.next() {
+        let value = match entries.next() {
+            Some(value) => value,
+            None => {
+                return Err(RuuviError::Malformed(format!(
+                    "Missing value for manufacturer {manufacturer_key:?}"
+                )))
+            }
+        };
+        match manufacturer_key.as_u64() {
+            Some(id) if id == u64::from(RUUVI_MANUFACTURER_ID) => {
+                ruuvi_data = Some(value);
+                break;
+            }
+            Some(id) => found.push(format!("{id:#06X}")),
+            None => found.push(format!("{manufacturer_key:?}")),
+        }
+    }
+    let ruuvi_data = match ruuvi_data {
+        Some(x) => x,
+        None => return Err(RuuviError::MissingManufacturerData { found }),
+    };
+    // The value is a `Variant` of one list so make it a iterable and take the first element.
+    let mut items = ruuvi_data
+        .as_iter()
+        .and_then(|mut x| x.next())
+        .and_then(|x| x.as_iter())
+        .ok_or_else(|| {
+            RuuviError::Malformed("Ruuvi manufacturer data isn't an array".to_string())
+        })?;
+
+    let mut payload = ManufacturerPayload {
+        bytes: [0; MAX_PAYLOAD_LENGTH],
+        len: 0,
+    };
+    let mut index = 0;
+    while let Some(item) = items.next() {
+        let byte = item
+            .as_i64()
+            .and_then(|x| u8::try_from(x).ok())
+            .ok_or_else(|| {
+                RuuviError::Malformed(format!(
+                    "Ruuvi manufacturer data element {index} isn't a byte: {item:?}"
+                ))
+            })?;
+        match payload.bytes.get_mut(index) {
+            Some(slot) => *slot = byte,
+            None => {
+                return Err(RuuviError::WrongLength {
+                    expected: MAX_PAYLOAD_LENGTH,
+                    got: index + 1 + items.count(),
+                })
+            }
+        }
+        index += 1;
+        payload.len = index;
+    }
+    Ok(payload)
+}
+
 /// Returns a mpsc channel that sends ruuvitag data.
 ///
-/// Subscribe to a ruuvitag by given `mac_address` and returns a mpsc channel that sends `Ruuvitag`
-/// information.
+/// Subscribe to a ruuvitag by given `mac_address` and returns a tokio mpsc channel that sends
+/// `Ruuvitag` information.
+///
+/// `mac_address` is anything convertible to a [`MacAddress`], e.g. a `&str` with colon- or
+/// dash-separated hex octets or a `MacAddress`. A malformed mac address is rejected with
+/// `RuuviError::InvalidMac` before connecting to D-Bus.
+///
+/// Currently only supports ruuvitag V5 format. Readings of the other data formats, e.g. of a tag
+/// in URL mode, are reported to `SubscribeOptions::errors` as `RuuviError::UnsupportedFormat`.
+/// Use `subscribe_ruuvitags_with_options` to receive them.
+///
+/// # Delivery ordering
+///
+/// Readings of the tag are sent in the order bluez emitted them. Every call opens its own D-Bus
+/// connection, processing task and channel, so a chatty tag subscribed with one call can't
+/// delay the readings of a tag subscribed with another. `subscribe_ruuvitags` shares a
+/// connection and a channel between several tags and takes turns between them instead.
+///
+/// # Backpressure
+///
+/// The channel holds up to `DEFAULT_CHANNEL_CAPACITY` readings. Once it is full the subscription
+/// waits for the consumer to catch up before forwarding further readings. Use
+/// `subscribe_ruuvitag_with_capacity` to change the capacity.
+///
+/// # Stopping
+///
+/// Dropping the receiver stops the subscription: the background task notices it right away,
+/// removes the signal match and closes the D-Bus connection.
+/// Use `subscribe_ruuvitag_with_cancel` to stop the subscription with a `CancellationToken`.
+///
+/// If the D-Bus connection is lost, e.g. because the D-Bus daemon restarted, the subscription
+/// stops and the receiver returns `None` once the buffered readings have been received. Use
+/// `SubscribeOptions::reconnect` to reconnect instead.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut rx = subscribe_ruuvitag(mac).await?;
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     current_sensor_data.print_sensor_data();
+/// }
+/// ```
+pub async fn subscribe_ruuvitag<M>(mac_address: M) -> Result<Receiver<SensorDataV5>, RuuviError>
+where
+    M: TryInto<MacAddress>,
+    RuuviError: From<M::Error>,
+{
+    let mac_address = mac_address.try_into()?;
+    let (rx, _handle) = subscribe_ruuvitag_with_stats(&mac_address.to_string()).await?;
+    Ok(rx)
+}
+
+/// Returns a mpsc channel holding up to `capacity` readings that sends ruuvitag data.
 ///
-/// Currently only supports ruuvitag V5 format.
+/// Works like `subscribe_ruuvitag`, but with the given channel capacity. A small capacity makes a
+/// slow consumer hold back the subscription sooner, a large one absorbs longer stalls of the
+/// consumer.
 ///
 /// # Panics
 ///
-/// The returned mpsc channel will panic if the receiver stops receiving. (Currently a bug that
-/// requires refactoring of code to use async dbus implementation.)
+/// If `capacity` is zero.
 ///
 /// # Examples
 ///
 /// ```
 /// // Replace with your mac address.
 /// let mac = "CC:6F:70:EE:4C:AD";
-/// let rx = subscribe_ruuvitag(&mac).await?;
+/// let mut rx = subscribe_ruuvitag_with_capacity(&mac, 4).await?;
 /// loop {
-///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
 ///     current_sensor_data.print_sensor_data();
 /// }
 /// ```
-pub async fn subscribe_ruuvitag(
+pub async fn subscribe_ruuvitag_with_capacity(
     mac_address: &str,
-) -> Result<Receiver<SensorDataV5>, Box<(dyn Error + 'static)>> {
-    let (tx, rx) = channel();
-    let conn = connect_bluetooth()?;
-    let mac_dbus_format = mac_address.replace(':', "_");
-    let mac_address = format!("dev_{mac_dbus_format}");
-    let proxy = conn.with_proxy(
-        "org.bluez",
-        format!("/org/bluez/hci0/{mac_address}"),
-        Duration::from_millis(20),
-    );
-    let _id = proxy.match_signal(
-        move |h: PropertiesPropertiesChanged, _: &Connection, _: &Message| {
-            let tag_data =
-                SensorDataV5::from_dbus_changed_properties(h.changed_properties).unwrap();
-            // Cannot currently gracefully shutdown if receiver gets dropped before sender does.
-            // Probably because dbus system bus is implemented as sync.
-            // This will lead to panics, if the receiver gets dropped.
-            // TBD: reimplement in dbus-tokio.
-            // https://docs.rs/dbus-tokio/latest/dbus_tokio/connection/index.html
-            tx.send(tag_data).unwrap();
-
-            true
-        },
-    );
-    tokio::spawn(async move {
-        loop {
-            conn.process(Duration::from_millis(20)).unwrap();
+    capacity: usize,
+) -> Result<Receiver<SensorDataV5>, RuuviError> {
+    subscribe(SubscribeOptions::new(mac_address).capacity(capacity)).await
+}
+
+/// Returns a mpsc channel that sends ruuvitag data received by the bluetooth adapter `adapter`.
+///
+/// Works like `subscribe_ruuvitag`, but scans with the given adapter, e.g. `hci1` of a USB dongle,
+/// instead of `hci0`. The adapter is reported by `SensorDataV5::adapter` of the readings.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut rx = subscribe_ruuvitag_with_adapter(&mac, "hci1").await?;
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     current_sensor_data.print_sensor_data();
+/// }
+/// ```
+pub async fn subscribe_ruuvitag_with_adapter(
+    mac_address: &str,
+    adapter: &str,
+) -> Result<Receiver<SensorDataV5>, RuuviError> {
+    subscribe(SubscribeOptions::new(mac_address).adapter(adapter)).await
+}
+
+/// Returns a mpsc channel that sends ruuvitag data over a connection set up with `options`.
+///
+/// Works like `subscribe_ruuvitag_with_adapter`, but the timeout of the method calls setting up
+/// the subscription can be configured as well. See `ConnectOptions` for how the timeout differs
+/// from the delivery of the readings.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::bluetooth::ConnectOptions;
+/// use std::time::Duration;
+///
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let options = ConnectOptions {
+///     adapter: "hci1".to_string(),
+///     timeout: Duration::from_secs(1),
+///     ..ConnectOptions::default()
+/// };
+/// let mut rx = subscribe_ruuvitag_with_options(&mac, &options).await?;
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     current_sensor_data.print_sensor_data();
+/// }
+/// ```
+pub async fn subscribe_ruuvitag_with_options(
+    mac_address: &str,
+    options: &ConnectOptions,
+) -> Result<Receiver<SensorDataV5>, RuuviError> {
+    subscribe(
+        SubscribeOptions::new(mac_address)
+            .adapter(options.adapter.clone())
+            .timeout(options.timeout),
+    )
+    .await
+}
+
+/// Returns a mpsc channel that sends ruuvitag data until `cancel` is cancelled.
+///
+/// Works like `subscribe_ruuvitag`, but the subscription can also be stopped by cancelling
+/// `cancel`. On cancellation the background task stops the discovery it started on the adapter,
+/// removes the signal match and closes the D-Bus connection. The channel is closed once the task
+/// has exited, so the receiver returns the readings still in the channel followed by `None`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tokio_util::sync::CancellationToken;
+///
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let cancel = CancellationToken::new();
+/// let mut rx = subscribe_ruuvitag_with_cancel(&mac, cancel.clone()).await?;
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_secs(60)).await;
+///     cancel.cancel();
+/// });
+/// while let Some(current_sensor_data) = rx.recv().await {
+///     current_sensor_data.print_sensor_data();
+/// }
+/// ```
+pub async fn subscribe_ruuvitag_with_cancel(
+    mac_address: &str,
+    cancel: CancellationToken,
+) -> Result<Receiver<SensorDataV5>, RuuviError> {
+    subscribe(SubscribeOptions::new(mac_address).cancel(cancel)).await
+}
+
+/// Returns a mpsc channel that sends ruuvitag data and a channel that sends the errors of the
+/// subscription.
+///
+/// Works like `subscribe_ruuvitag`, but the errors the background task would otherwise only
+/// count as dropped are sent to the second channel: the errors of signals that couldn't be
+/// decoded and `RuuviError::ConnectionLost` if the D-Bus connection is lost. See
+/// `SubscribeOptions::errors`.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let (mut rx, mut errors) = subscribe_ruuvitag_with_errors(mac).await?;
+/// tokio::spawn(async move {
+///     while let Some(error) = errors.recv().await {
+///         eprintln!("{error}");
+///     }
+/// });
+/// while let Some(current_sensor_data) = rx.recv().await {
+///     current_sensor_data.print_sensor_data();
+/// }
+/// ```
+pub async fn subscribe_ruuvitag_with_errors(
+    mac_address: &str,
+) -> Result<(Receiver<SensorDataV5>, Receiver<RuuviError>), RuuviError> {
+    let (errors_tx, errors_rx) = channel(DEFAULT_CHANNEL_CAPACITY);
+    let rx = subscribe(SubscribeOptions::new(mac_address).errors(errors_tx)).await?;
+    Ok((rx, errors_rx))
+}
+
+/// Returns a broadcast channel that sends ruuvitag data to multiple consumers.
+///
+/// Works like `subscribe_ruuvitag`, but every receiver gets every reading, e.g. to log the
+/// readings to disk and update a UI from the same subscription. Get a receiver for another
+/// consumer with `rx.resubscribe()`; it receives the readings sent after it was created.
+///
+/// The channel holds `DEFAULT_CHANNEL_CAPACITY` readings. A receiver that falls further behind
+/// misses the oldest readings and its next `recv` returns `RecvError::Lagged` with the number of
+/// readings it missed, after which it continues from the oldest reading still held. The
+/// subscription stops after all of the receivers have been dropped, once the next reading
+/// arrives.
+///
+/// # Examples
+///
+/// ```
+/// use tokio::sync::broadcast::error::RecvError;
+///
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut rx = subscribe_ruuvitag_broadcast(mac).await?;
+/// let mut log_rx = rx.resubscribe();
+/// tokio::spawn(async move {
+///     loop {
+///         match log_rx.recv().await {
+///             Ok(current_sensor_data) => log.write_all(current_sensor_data.to_json().as_bytes())?,
+///             Err(RecvError::Lagged(missed)) => eprintln!("missed {missed} readings"),
+///             Err(RecvError::Closed) => break,
+///         }
+///     }
+/// });
+/// while let Ok(current_sensor_data) = rx.recv().await {
+///     label.set_text(&current_sensor_data.summary());
+/// }
+/// ```
+pub async fn subscribe_ruuvitag_broadcast(
+    mac_address: &str,
+) -> Result<broadcast::Receiver<SensorDataV5>, RuuviError> {
+    let rx = subscribe_ruuvitag(mac_address).await?;
+    let (tx, broadcast_rx) = broadcast::channel(DEFAULT_CHANNEL_CAPACITY);
+    tokio::spawn(forward_broadcast(rx, tx));
+    Ok(broadcast_rx)
+}
+
+/// Sends the readings received by `rx` to the receivers of `tx` until either all of the
+/// receivers of `tx` have been dropped or `rx` is closed.
+async fn forward_broadcast<T>(mut rx: Receiver<T>, tx: broadcast::Sender<T>) {
+    while let Some(reading) = rx.recv().await {
+        if tx.send(reading).is_err() {
+            break;
         }
-    });
+    }
+}
+
+/// Returns a mpsc channel that sends ruuvitag data and a handle to the subscription.
+///
+/// Works like `subscribe_ruuvitag`, but the returned `SubscriptionHandle` can be used to read the
+/// statistics of the subscription from another thread or task, e.g. a metrics endpoint.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let (mut rx, handle) = subscribe_ruuvitag_with_stats(&mac).await?;
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     println!("{:?}", handle.stats());
+/// }
+/// ```
+pub async fn subscribe_ruuvitag_with_stats(
+    mac_address: &str,
+) -> Result<(Receiver<SensorDataV5>, SubscriptionHandle), RuuviError> {
+    subscribe_ruuvitag_with_label(mac_address, mac_address).await
+}
+
+/// Returns a mpsc channel that sends ruuvitag data and a handle to the subscription labeled with
+/// `label`.
+///
+/// Works like `subscribe_ruuvitag_with_stats`, but the subscription is labeled with `label`
+/// instead of the mac address. With the `tracing` feature the label is included as the
+/// `subscription` field of every event of the subscription, which tells the subscriptions apart
+/// in the logs of a multi-tag deployment.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let (mut rx, handle) = subscribe_ruuvitag_with_label(&mac, "sauna").await?;
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     println!("{}: {:?}", handle.label(), handle.stats());
+/// }
+/// ```
+pub async fn subscribe_ruuvitag_with_label(
+    mac_address: &str,
+    label: impl Into<String>,
+) -> Result<(Receiver<SensorDataV5>, SubscriptionHandle), RuuviError> {
+    subscribe_with_handle(
+        SubscribeOptions::new(mac_address).label(label),
+        |data, _| data,
+    )
+    .await
+}
+
+/// Returns a mpsc channel that sends ruuvitag data together with the time it was received.
+///
+/// Works like `subscribe_ruuvitag`, but every reading is stamped with the time the D-Bus signal
+/// carrying it was processed. Readings waiting in a full channel keep the time they were
+/// received, so time series writers get accurate timestamps even when they fall behind.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut rx = subscribe_ruuvitag_timestamped(&mac).await?;
+/// loop {
+///     let reading: TimestampedReading = rx.recv().await.unwrap();
+///     println!("{}", reading.data.to_influx_line("ruuvi", Some(reading.received_at)));
+/// }
+/// ```
+pub async fn subscribe_ruuvitag_timestamped(
+    mac_address: &str,
+) -> Result<Receiver<TimestampedReading>, RuuviError> {
+    let (rx, _handle) =
+        subscribe_with_handle(SubscribeOptions::new(mac_address), |data, received_at| {
+            TimestampedReading { received_at, data }
+        })
+        .await?;
     Ok(rx)
 }
 
-/// A structure to hold ruuvitag data from V5 format.
+/// A reading together with the time it was received, sent by `subscribe_ruuvitag_timestamped`.
+#[derive(Debug, Clone)]
+pub struct TimestampedReading {
+    pub received_at: SystemTime,
+    pub data: SensorDataV5,
+}
+
+/// Options of a subscription to a ruuvitag.
 ///
-/// TODO: max numbers such as i32::MAX should be considered as invalid/data not available
-/// Implementation following ruuvi data format 5
-/// <https://github.com/ruuvi/ruuvi-sensor-protocols/blob/master/dataformat_05.md>
-#[derive(Debug)]
-pub struct SensorDataV5 {
-    temperature: i16,
-    humidity: u16,
-    pressure: u16,
-    pub acceleration: Acceleration,
-    power_info: u16,
-    pub movement_counter: u8,
-    pub measurement_number: u16,
-    mac: [u8; 6],
+/// Constructed with the mac address of the tag, the rest of the options default to the behavior
+/// of `subscribe_ruuvitag` and can be changed with the builder methods.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::{subscribe, SubscribeOptions};
+/// use std::time::Duration;
+///
+/// // Replace with your mac address.
+/// let options = SubscribeOptions::new("CC:6F:70:EE:4C:AD")
+///     .adapter("hci1")
+///     .timeout(Duration::from_secs(1))
+///     .capacity(64);
+/// let mut rx = subscribe(options).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubscribeOptions {
+    mac_address: String,
+    label: Option<String>,
+    connect: ConnectOptions,
+    capacity: usize,
+    cancel: CancellationToken,
+    verify_mac: bool,
+    errors: Option<Sender<RuuviError>>,
+    reconnect: Option<ReconnectOptions>,
 }
 
-impl SensorDataV5 {
-    /// Constructs a new `SensorDataV5`.
-    pub fn new(
-        temperature: i16,
-        humidity: u16,
-        pressure: u16,
-        acceleration: Acceleration,
-        power_info: u16,
-        movement_counter: u8,
-        measurement_number: u16,
-        mac: [u8; 6],
-    ) -> Self {
+impl SubscribeOptions {
+    /// Constructs the default options of a subscription to the ruuvitag `mac_address`.
+    pub fn new(mac_address: impl Into<String>) -> Self {
         Self {
-            temperature,
-            humidity,
-            pressure,
-            acceleration,
-            power_info,
-            movement_counter,
-            measurement_number,
-            mac,
+            mac_address: mac_address.into(),
+            label: None,
+            connect: ConnectOptions::default(),
+            capacity: DEFAULT_CHANNEL_CAPACITY,
+            cancel: CancellationToken::new(),
+            verify_mac: false,
+            errors: None,
+            reconnect: None,
         }
     }
 
-    /// Constructs a `SensorDataV5` from dbus message `PropertiesChanged`.
-    pub fn from_dbus_changed_properties(changed_properties: arg::PropMap) -> Result<Self, String> {
-        let data: Vec<&dyn arg::RefArg> = match changed_properties["ManufacturerData"].0.as_iter() {
-            Some(x) => x.collect(),
-            None => return Err("ManufacturerData couldn't be collected".to_string()),
-        };
-        if data.len() != 2 {
-            return Err(format!("Missing data in changed_properties {data:?}"));
-        }
-        let _manufacturer_key = data[0];
-        // data[1] is a `Variant` of one list so make it a iterable and take the first element.
-        let manufacturer_data = data[1].as_iter().unwrap().next().unwrap();
+    /// Labels the subscription with `label` instead of the mac address.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
 
-        let mut temp: Vec<u8> = Vec::new();
-        for item in manufacturer_data.as_iter().unwrap() {
-            temp.push(item.as_i64().unwrap() as u8);
-        }
-        if temp.len() != 24 {
-            return Err(format!("Missing manufacturer data {temp:?}"));
-        }
-        // TODO: Assert the data format that it is V5.
-        let _data_format = temp[0];
-        let temperature = join_u8(temp[1], temp[2]) as i16;
-        let humidity = join_u8(temp[3], temp[4]);
-        let pressure = join_u8(temp[5], temp[6]);
-        let acceleration = Acceleration {
-            x: join_u8(temp[7], temp[8]) as i16,
-            y: join_u8(temp[9], temp[10]) as i16,
-            z: join_u8(temp[11], temp[12]) as i16,
-        };
-        let power_info = join_u8(temp[13], temp[14]);
-        let movement_counter = temp[15];
-        let measurement_number = join_u8(temp[16], temp[17]);
-        let mac: [u8; 6] = [temp[18], temp[19], temp[20], temp[21], temp[22], temp[23]];
+    /// Scans with the bluetooth adapter `adapter` instead of `hci0`.
+    pub fn adapter(mut self, adapter: impl Into<String>) -> Self {
+        self.connect.adapter = adapter.into();
+        self
+    }
 
-        Ok(SensorDataV5::new(
-            temperature,
-            humidity,
-            pressure,
-            acceleration,
-            power_info,
-            movement_counter,
-            measurement_number,
-            mac,
-        ))
+    /// Sets the timeout of the method calls setting up the subscription, see `ConnectOptions`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.connect.timeout = timeout;
+        self
     }
 
-    /// Returns the current temperature measured from ruuvitag in millicelsius.
-    ///
-    /// # Examples
+    /// Sets the discovery filter of the connection, see `ConnectOptions::discovery_filter`.
     ///
-    /// ```
-    /// // Replace with your mac address.
-    /// let mac = "CC:6F:70:EE:4C:AD";
-    /// let rx = subscribe_ruuvitag(&mac).await?;
-    /// loop {
-    ///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-    ///     println!("{}", current_sensor_data.temperature_in_millicelcius());
-    /// }
-    /// ```
-    pub fn temperature_in_millicelcius(&self) -> i32 {
-        // TODO: optimization wise it might be better to set self.temperature as i32 so we don't
-        // need to cast it everytime. though memory wise it would be better to use i16 but I think
-        // compiler might do this for us.
-        i32::try_from(self.temperature).unwrap() * 5
+    /// E.g. `DiscoveryFilter::duplicate_data` receives every advertisement of the tag.
+    pub fn discovery_filter(mut self, discovery_filter: Option<DiscoveryFilter>) -> Self {
+        self.connect.discovery_filter = discovery_filter;
+        self
     }
-    /// Returns the current temperature measured from ruuvitag in celsius.
+
+    /// Sets the number of readings the channel holds.
     ///
-    /// # Examples
+    /// # Panics
     ///
-    /// ```
-    /// // Replace with your mac address.
-    /// let mac = "CC:6F:70:EE:4C:AD";
-    /// let rx = subscribe_ruuvitag(&mac).await?;
-    /// loop {
-    ///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-    ///     println!("{}", current_sensor_data.temperature_in_celcius());
-    /// }
-    /// ```
-    pub fn temperature_in_celcius(&self) -> f64 {
-        self.temperature_in_millicelcius() as f64 / 1000_f64
+    /// Subscribing panics if `capacity` is zero.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
     }
-    /// Returns the current humidity % measured from ruuvitag.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// // Replace with your mac address.
-    /// let mac = "CC:6F:70:EE:4C:AD";
-    /// let rx = subscribe_ruuvitag(&mac).await?;
-    /// loop {
-    ///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-    ///     println!("{}", current_sensor_data.get_humidity());
-    /// }
-    /// ```
-    pub fn get_humidity(&self) -> f64 {
-        self.humidity as f64 / 400_f64
+
+    /// Stops the subscription once `cancel` is cancelled, see `subscribe_ruuvitag_with_cancel`.
+    pub fn cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
     }
-    /// Returns the current air pressure hPa measured from ruuvitag.
-    ///
-    /// # Examples
+
+    /// Drops readings whose payload mac address differs from the subscribed one.
     ///
-    /// ```
-    /// // Replace with your mac address.
-    /// let mac = "CC:6F:70:EE:4C:AD";
-    /// let rx = subscribe_ruuvitag(&mac).await?;
-    /// loop {
-    ///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-    ///     println!("{}", current_sensor_data.get_pressure());
-    /// }
-    /// ```
-    pub fn get_pressure(&self) -> u32 {
-        50000 + self.pressure as u32
+    /// The data format 5 payload carries the mac address of the tag itself, which catches
+    /// readings attributed to the wrong tag, e.g. by a stale bluez cache. Rejected readings are
+    /// counted as dropped and logged as `RuuviError::MacMismatch` with the `tracing` feature.
+    /// Off by default, as tags using random addresses advertise a different mac address than the
+    /// one in their payload.
+    pub fn verify_mac(mut self, verify_mac: bool) -> Self {
+        self.verify_mac = verify_mac;
+        self
     }
-    /// Returns the current `Acceleration` mG measured from ruuvitag.
-    ///
-    /// # Examples
+
+    /// Sends the errors of the subscription to `errors`.
     ///
-    /// ```
-    /// // Replace with your mac address.
-    /// let mac = "CC:6F:70:EE:4C:AD";
-    /// let rx = subscribe_ruuvitag(&mac).await?;
-    /// loop {
-    ///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-    ///     println!("{:?}", current_sensor_data.get_acceleration_in_mg());
-    /// }
-    /// ```
-    pub fn get_acceleration_in_mg(&self) -> &Acceleration {
-        &self.acceleration
+    /// The errors are the ones of signals that couldn't be decoded, readings of other data
+    /// formats than 5, readings rejected by `verify_mac` and `RuuviError::ConnectionLost`. They
+    /// are sent without waiting, so errors are discarded while the channel is full rather than
+    /// holding back the readings.
+    pub fn errors(mut self, errors: Sender<RuuviError>) -> Self {
+        self.errors = Some(errors);
+        self
     }
-    /// Returns the current battery voltage (mV) measured from ruuvitag.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// // Replace with your mac address.
-    /// let mac = "CC:6F:70:EE:4C:AD";
-    /// let rx = subscribe_ruuvitag(&mac).await?;
-    /// loop {
-    ///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-    ///     println!("{}", current_sensor_data.get_battery_voltage());
-    /// }
-    /// ```
-    pub fn get_battery_voltage(&self) -> u16 {
-        let power_info = self.power_info;
-        // battery voltage in millivolts
-        let mut battery_mv = power_info >> 5;
-        battery_mv += BATTERY_OFFSET;
-        battery_mv
+
+    /// Reconnects the subscription as configured by `reconnect` when the D-Bus connection is
+    /// lost or the tag falls silent, instead of ending the subscription.
+    pub fn reconnect(mut self, reconnect: ReconnectOptions) -> Self {
+        self.reconnect = Some(reconnect);
+        self
     }
+}
 
-    /// Returns the current transmit power (dBm) measured from ruuvitag.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// // Replace with your mac address.
-    /// let mac = "CC:6F:70:EE:4C:AD";
-    /// let rx = subscribe_ruuvitag(&mac).await?;
-    /// loop {
-    ///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-    ///     println!("{}", current_sensor_data.get_tx_power());
-    /// }
-    /// ```
-    pub fn get_tx_power(&self) -> i8 {
-        let power_info = self.power_info;
-        // TX power in decibel millivolts
-        let mut tx_power_dbm = (power_info & 0x1f) as i8 * 2;
-        tx_power_dbm += TX_POWER_OFFSET;
-        tx_power_dbm
+/// Options of reconnecting a subscription, see `SubscribeOptions::reconnect`.
+///
+/// A reconnection connects to D-Bus again, powers on the adapter, restarts discovery and
+/// registers the signal match of the tag again. Failed attempts are retried after a delay that
+/// starts at `backoff` and doubles after every attempt up to `max_backoff`. The attempts are
+/// counted from the latest reading, so a subscription that recovered gets `max_retries` attempts
+/// again the next time. Errors of the attempts are sent to `SubscribeOptions::errors`.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::{subscribe, ReconnectOptions, SubscribeOptions};
+/// use std::time::Duration;
+///
+/// // Replace with your mac address.
+/// let options = SubscribeOptions::new("CC:6F:70:EE:4C:AD").reconnect(ReconnectOptions {
+///     silence_timeout: Some(Duration::from_secs(60)),
+///     ..ReconnectOptions::default()
+/// });
+/// let mut rx = subscribe(options).await?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectOptions {
+    /// Number of failed attempts in a row after which the subscription ends, `None` to retry
+    /// forever. `None` by default.
+    pub max_retries: Option<u32>,
+    /// Delay before the first attempt. 1 second by default.
+    pub backoff: Duration,
+    /// Upper bound of the delay between attempts. 60 seconds by default.
+    pub max_backoff: Duration,
+    /// Reconnects if no reading has been received for this long, e.g. because the bluez device
+    /// object of the tag disappeared. `None` by default to only reconnect on connection loss.
+    pub silence_timeout: Option<Duration>,
+}
+
+impl ReconnectOptions {
+    /// Returns the delay before the reconnection attempt `attempt`, counted from zero.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_backoff)
     }
+}
 
-    /// Returns the mac address of the measured ruuvitag.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// // Replace with your mac address.
-    /// let mac = "CC:6F:70:EE:4C:AD";
-    /// let rx = subscribe_ruuvitag(&mac).await?;
-    /// loop {
-    ///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-    ///     println!("{}", current_sensor_data.mac_as_str());
-    /// }
-    /// ```
-    pub fn mac_as_str(&self) -> String {
-        self.mac
-            .iter()
-            .map(|x| format!("{:02X}", x))
-            .collect::<Vec<String>>()
-            .join(":")
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: None,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            silence_timeout: None,
+        }
     }
+}
 
-    /// Prints diagnostic information about the measured ruuvitag.
-    ///
-    /// Prints all of the data measured by the ruuvitag in a
-    /// diagnostic format to ease development.
-    ///
-    /// # Examples
+/// Returns a mpsc channel that sends ruuvitag data of a subscription configured by `options`.
+///
+/// `subscribe_ruuvitag` and its variants are shorthands of this function with the corresponding
+/// option set.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// // Replace with your mac address.
+/// let options = SubscribeOptions::new("CC:6F:70:EE:4C:AD")
+///     .timeout(Duration::from_secs(1))
+///     .capacity(64);
+/// let mut rx = subscribe(options).await?;
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     current_sensor_data.print_sensor_data();
+/// }
+/// ```
+pub async fn subscribe(options: SubscribeOptions) -> Result<Receiver<SensorDataV5>, RuuviError> {
+    let (rx, _handle) = subscribe_with_handle(options, |data, _| data).await?;
+    Ok(rx)
+}
+
+/// Subscribes to a ruuvitag with `options` and returns a handle to the subscription as well.
+///
+/// The subscription runs until its receiver is dropped or its cancellation token is cancelled,
+/// or until the D-Bus connection is lost and can't be reconnected.
+///
+/// Every decoded reading is passed to `wrap` together with the time it was received, and the
+/// result is sent to the channel.
+async fn subscribe_with_handle<T, F>(
+    options: SubscribeOptions,
+    wrap: F,
+) -> Result<(Receiver<T>, SubscriptionHandle), RuuviError>
+where
+    T: Send + 'static,
+    F: Fn(SensorDataV5, SystemTime) -> T + Send + 'static,
+{
+    let SubscribeOptions {
+        mac_address,
+        label,
+        connect,
+        capacity,
+        cancel,
+        verify_mac,
+        errors,
+        reconnect,
+    } = options;
+    let mac: MacAddress = mac_address.parse()?;
+    let (tx, rx) = channel(capacity);
+    let counters = Arc::new(SubscriptionCounters::default());
+    let handle = SubscriptionHandle {
+        label: Arc::from(label.unwrap_or_else(|| mac_address.clone())),
+        counters: Arc::clone(&counters),
+    };
+    #[cfg(feature = "tracing")]
+    let label = Arc::clone(&handle.label);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(subscription = %label, mac = %mac_address, "subscribing to ruuvitag");
+    let mut session = connect_session(&connect, &mac).await?;
+    let adapter = connect.adapter.clone();
+    let mut decoder = TagDecoder::new(mac, adapter.clone(), None);
+    tokio::spawn(async move {
+        let mut attempt = 0;
+        'subscription: loop {
+            let (conn, mut resource_task, signal_match, mut signals) = session;
+            let silence_timeout = reconnect.and_then(|reconnect| reconnect.silence_timeout);
+            let mut silent_since = tokio::time::Instant::now();
+            let stopped = loop {
+                let silence = async move {
+                    match silence_timeout {
+                        Some(silence_timeout) => {
+                            tokio::time::sleep_until(silent_since + silence_timeout).await
+                        }
+                        None => std::future::pending().await,
+                    }
+                };
+                let h = tokio::select! {
+                    signal = signals.next() => match signal {
+                        Some((_, h)) => h,
+                        None => break Stopped::Closed,
+                    },
+                    _ = tx.closed() => break Stopped::Closed,
+                    _ = cancel.cancelled() => break Stopped::Cancelled,
+                    _ = &mut resource_task => break Stopped::Disconnected,
+                    _ = silence => break Stopped::Silent,
+                };
+                let received_at = SystemTime::now();
+                counters.seen.fetch_add(1, Ordering::Relaxed);
+                // Updates of only the RSSI or the connection state carry no reading.
+                let Some(reading) = decoder.decode(h.changed_properties) else {
+                    continue;
+                };
+                // Readings of the other data formats, e.g. of a tag in URL mode, are reported
+                // rather than dropped silently.
+                let tag_data = match reading.and_then(SensorDataV5::try_from) {
+                    Ok(tag_data) => tag_data,
+                    Err(e) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(
+                            subscription = %label,
+                            error = %e,
+                            "dropped undecodable signal"
+                        );
+                        counters.dropped.fetch_add(1, Ordering::Relaxed);
+                        report(&errors, e);
+                        continue;
+                    }
+                };
+                if verify_mac {
+                    if let Err(e) = tag_data.verify_mac(&mac) {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(subscription = %label, error = %e, "dropped reading");
+                        counters.dropped.fetch_add(1, Ordering::Relaxed);
+                        report(&errors, e);
+                        continue;
+                    }
+                }
+                counters.decoded.fetch_add(1, Ordering::Relaxed);
+                #[cfg(feature = "tracing")]
+                tracing::trace!(
+                    subscription = %label,
+                    measurement_number = tag_data.measurement_number(),
+                    "decoded reading"
+                );
+                silent_since = tokio::time::Instant::now();
+                attempt = 0;
+                tokio::select! {
+                    forwarded = forward(&tx, &counters, wrap(tag_data, received_at)) => if !forwarded {
+                        break Stopped::Closed;
+                    },
+                    _ = cancel.cancelled() => break Stopped::Cancelled,
+                }
+            };
+            match stopped {
+                Stopped::Closed => {
+                    let _ = conn.remove_match(signal_match.token()).await;
+                }
+                Stopped::Cancelled => {
+                    let _ = conn.remove_match(signal_match.token()).await;
+                    let _ =
+                        stop_discovery_async(&conn, &adapter_path(&adapter), connect.timeout).await;
+                }
+                // The connection is gone, so there is nothing to clean up. Unless reconnecting,
+                // dropping `tx` ends the receiver instead of leaving it waiting for readings that
+                // never arrive.
+                Stopped::Disconnected => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(subscription = %label, "subscription lost its D-Bus connection");
+                    report(&errors, RuuviError::ConnectionLost);
+                }
+                Stopped::Silent => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(subscription = %label, "no readings received, reconnecting");
+                    let _ = conn.remove_match(signal_match.token()).await;
+                }
+            }
+            resource_task.abort();
+            let (Stopped::Disconnected | Stopped::Silent, Some(reconnect)) = (stopped, reconnect)
+            else {
+                break;
+            };
+            session = loop {
+                if reconnect
+                    .max_retries
+                    .is_some_and(|max_retries| attempt >= max_retries)
+                {
+                    break 'subscription;
+                }
+                let backoff = reconnect.backoff(attempt);
+                attempt += 1;
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {},
+                    _ = tx.closed() => break 'subscription,
+                    _ = cancel.cancelled() => break 'subscription,
+                }
+                #[cfg(feature = "tracing")]
+                tracing::debug!(subscription = %label, attempt, "reconnecting to ruuvitag");
+                match connect_session(&connect, &mac).await {
+                    Ok(session) => break session,
+                    Err(e) => report(&errors, e),
+                }
+            };
+        }
+        counters.closed.store(true, Ordering::Relaxed);
+    });
+    Ok((rx, handle))
+}
+
+/// Connects to D-Bus with `connect` and registers a signal match for the ruuvitag `mac`.
+///
+/// Returns the connection, the task driving it, the match and the stream of its signals.
+async fn connect_session(
+    connect: &ConnectOptions,
+    mac: &MacAddress,
+) -> Result<
+    (
+        Arc<SyncConnection>,
+        JoinHandle<()>,
+        MsgMatch,
+        impl Stream<Item = (Message, PropertiesPropertiesChanged)> + Unpin + Send,
+    ),
+    RuuviError,
+> {
+    let (conn, resource_task) = connect_bluetooth_async(connect).await?;
+    let rule = PropertiesPropertiesChanged::match_rule(
+        Some(&"org.bluez".into()),
+        Some(&mac.device_path(&connect.adapter).into()),
+    )
+    .static_clone();
+    match conn.add_match(rule).await {
+        Ok(signal_match) => {
+            let (signal_match, signals) = signal_match.stream::<PropertiesPropertiesChanged>();
+            Ok((conn, resource_task, signal_match, signals))
+        }
+        Err(e) => {
+            resource_task.abort();
+            Err(e.into())
+        }
+    }
+}
+
+/// Sends `error` to the error channel of a subscription, if any, without waiting for room.
+fn report(errors: &Option<Sender<RuuviError>>, error: RuuviError) {
+    if let Some(errors) = errors {
+        let _ = errors.try_send(error);
+    }
+}
+
+/// Why the processing task of a subscription stopped.
+#[derive(Debug, Clone, Copy)]
+enum Stopped {
+    /// The receiver was dropped or the signal stream ended.
+    Closed,
+    /// The cancellation token of the subscription was cancelled.
+    Cancelled,
+    /// The D-Bus connection was lost.
+    Disconnected,
+    /// No reading was received within `ReconnectOptions::silence_timeout`.
+    Silent,
+}
+
+/// Sends a reading to the receiver of a subscription.
+///
+/// Waits for room in the channel if it is full. Returns `false` if the receiver has been
+/// dropped, in which case the subscription is marked closed and the processing task exits.
+async fn forward<T>(tx: &Sender<T>, counters: &SubscriptionCounters, sensor_data: T) -> bool {
+    if tx.send(sensor_data).await.is_err() {
+        counters.closed.store(true, Ordering::Relaxed);
+        return false;
+    }
+    true
+}
+
+/// Counters of a subscription updated by the D-Bus processing.
+#[derive(Debug, Default)]
+struct SubscriptionCounters {
+    seen: AtomicU64,
+    decoded: AtomicU64,
+    dropped: AtomicU64,
+    /// Set once the subscription has stopped.
+    closed: AtomicBool,
+}
+
+/// Handle to a running subscription.
+///
+/// Cheap to clone and safe to share between threads and tasks.
+#[derive(Debug, Clone)]
+pub struct SubscriptionHandle {
+    label: Arc<str>,
+    counters: Arc<SubscriptionCounters>,
+}
+
+impl SubscriptionHandle {
+    /// Returns the label of the subscription, the mac address unless labeled otherwise.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns `true` once the subscription has stopped and sends no more readings.
     ///
-    /// ```
-    /// // Replace with your mac address.
-    /// let mac = "CC:6F:70:EE:4C:AD";
-    /// let rx = subscribe_ruuvitag(&mac).await?;
-    /// loop {
-    ///     let current_sensor_data: SensorDataV5 = rx.recv().unwrap();
-    ///     current_sensor_data.print_sensor_data();
-    /// }
-    /// ```
-    pub fn print_sensor_data(&self) {
-        println!("MAC address: {:?}", self.mac_as_str());
-        println!(
-            "temperature in millicelcius (°mC): {:?}",
-            self.temperature_in_millicelcius()
-        );
-        println!(
-            "temperature in celcius (°C): {:?}",
-            self.temperature_in_celcius()
-        );
-        println!("humidity (%): {:?}", self.get_humidity());
-        println!("Atmoshperic pressure (Pa): {:?}", self.get_pressure());
-        println!("Acceleration (mG): {:?}", self.get_acceleration_in_mg());
-        println!("Battery voltage (mV): {:?}", self.get_battery_voltage());
-        println!("Tx Power (dBm): {:?}", self.get_tx_power());
-        println!("Movement counter: {:?}", self.movement_counter);
-        println!("Measurement sequence number: {:?}", self.measurement_number);
-        println!();
+    /// The subscription stops when its receiver is dropped, when it is cancelled, or when its
+    /// D-Bus connection is lost and not re-established, either because it has no
+    /// `ReconnectOptions` or because they ran out of retries.
+    pub fn is_closed(&self) -> bool {
+        self.counters.closed.load(Ordering::Relaxed)
+    }
+
+    /// Returns a snapshot of the statistics of the subscription.
+    pub fn stats(&self) -> SubscriptionStats {
+        SubscriptionStats {
+            seen: self.counters.seen.load(Ordering::Relaxed),
+            decoded: self.counters.decoded.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+        }
     }
 }
 
-/// Structure to hold acceleration information (X, Y, Z)
+/// Snapshot of the statistics of a subscription.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubscriptionStats {
+    /// Number of `PropertiesChanged` signals received from the tag, including the ones without
+    /// manufacturer data, which are skipped.
+    pub seen: u64,
+    /// Number of signals decoded into sensor data.
+    pub decoded: u64,
+    /// Number of signals that couldn't be decoded or whose mac address didn't match, see
+    /// `SubscribeOptions::verify_mac`.
+    pub dropped: u64,
+}
+
+/// Subscribe to a ruuvitag by given `mac_address` and returns a `Stream` of its readings.
+///
+/// Works like `subscribe_ruuvitag`, but the readings can be composed with the adaptors of
+/// `StreamExt`, e.g. `map` or `filter`. Dropping the stream stops the subscription.
+///
+/// # Examples
+///
+/// Noisy tags can be rate-limited with `throttle` of `tokio_stream::StreamExt`:
+///
+/// ```
+/// use std::time::Duration;
+/// use tokio_stream::StreamExt;
+///
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let stream = subscribe_ruuvitag_stream(&mac).await?;
+/// let mut stream = Box::pin(stream.throttle(Duration::from_secs(10)));
+/// while let Some(current_sensor_data) = stream.next().await {
+///     current_sensor_data.print_sensor_data();
+/// }
+/// ```
+pub async fn subscribe_ruuvitag_stream(
+    mac_address: &str,
+) -> Result<impl Stream<Item = SensorDataV5>, RuuviError> {
+    let rx = subscribe_ruuvitag(mac_address).await?;
+    Ok(SensorDataStream { rx })
+}
+
+/// `Stream` of the readings of a subscription returned by `subscribe_ruuvitag_stream`.
 #[derive(Debug)]
-pub struct Acceleration {
-    pub x: i16,
-    pub y: i16,
-    pub z: i16,
+struct SensorDataStream {
+    rx: Receiver<SensorDataV5>,
 }
 
-impl Acceleration {
-    /// Constructs a `Acceleration` structure.
-    pub fn new(x: i16, y: i16, z: i16) -> Acceleration {
-        Acceleration { x, y, z }
+impl Stream for SensorDataStream {
+    type Item = SensorDataV5;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<SensorDataV5>> {
+        self.rx.poll_recv(cx)
     }
 }
 
-#[cfg(test)]
-mod tests {
+/// Blocking `Iterator` over the readings of a subscription.
+///
+/// For synchronous consumers running on a thread of their own, e.g. with `std::thread::spawn`
+/// or `tokio::task::spawn_blocking`. The iteration ends once the subscription stops, instead of
+/// panicking like `rx.blocking_recv().unwrap()` would. Works with the receiver of any
+/// subscription, e.g. `subscribe_ruuvitag` or `subscribe_ruuvitags`.
+///
+/// # Panics
+///
+/// Iterating panics if called within an asynchronous execution context, see
+/// `Receiver::blocking_recv`.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let rx = subscribe_ruuvitag(mac).await?;
+/// std::thread::spawn(move || {
+///     for current_sensor_data in ReadingIterator::new(rx) {
+///         current_sensor_data.print_sensor_data();
+///     }
+/// });
+/// ```
+#[derive(Debug)]
+pub struct ReadingIterator<T> {
+    rx: Receiver<T>,
+}
+
+impl<T> ReadingIterator<T> {
+    /// Constructs a `ReadingIterator` iterating the readings received by `rx`.
+    pub fn new(rx: Receiver<T>) -> Self {
+        Self { rx }
+    }
+}
 
-    use crate::ruuvitag::{Acceleration, SensorDataV5};
+impl<T> From<Receiver<T>> for ReadingIterator<T> {
+    fn from(rx: Receiver<T>) -> Self {
+        Self::new(rx)
+    }
+}
 
-    #[test]
-    fn test_ruuvitag_sensor_data_v5_min() {
-        let sensor_data = SensorDataV5::new(
-            i16::MIN,
-            u16::MIN,
-            u16::MIN,
-            Acceleration::new(i16::MIN, i16::MIN, i16::MIN),
-            u16::MIN,
-            u8::MIN,
-            u16::MIN,
-            [u8::MIN, u8::MIN, u8::MIN, u8::MIN, u8::MIN, u8::MIN],
+impl<T> Iterator for ReadingIterator<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.blocking_recv()
+    }
+}
+
+/// Non-blocking access to the most recent reading of a subscription.
+///
+/// The recommended pattern for polling consumers, e.g. a UI rendering at its own frame rate.
+/// Each call to `try_latest` drains the readings received since the previous call and returns
+/// only the most recent one, discarding the older backlog so the consumer never falls behind a
+/// fast-advertising tag. Works with the receiver of any subscription, but with
+/// `subscribe_ruuvitags` the readings of the other tags are discarded too.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut latest = LatestReading::new(subscribe_ruuvitag(mac).await?);
+/// while !latest.is_closed() {
+///     if let Some(current_sensor_data) = latest.try_latest() {
+///         label.set_text(&current_sensor_data.summary());
+///     }
+///     render_frame();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct LatestReading<T> {
+    rx: Receiver<T>,
+    closed: bool,
+}
+
+impl<T> LatestReading<T> {
+    /// Constructs a `LatestReading` polling the readings received by `rx`.
+    pub fn new(rx: Receiver<T>) -> Self {
+        Self { rx, closed: false }
+    }
+
+    /// Returns the most recent reading received since the previous call without waiting,
+    /// `None` if there is none.
+    ///
+    /// The older readings received since the previous call are discarded.
+    pub fn try_latest(&mut self) -> Option<T> {
+        let mut latest = None;
+        loop {
+            match self.rx.try_recv() {
+                Ok(reading) => latest = Some(reading),
+                Err(TryRecvError::Empty) => return latest,
+                Err(TryRecvError::Disconnected) => {
+                    self.closed = true;
+                    return latest;
+                }
+            }
+        }
+    }
+
+    /// Returns `true` once the subscription has stopped and all of its readings have been
+    /// returned by `try_latest`.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+impl<T> From<Receiver<T>> for LatestReading<T> {
+    fn from(rx: Receiver<T>) -> Self {
+        Self::new(rx)
+    }
+}
+
+/// Returns a mpsc channel that sends ruuvitag data of the tag with the given bluez name.
+///
+/// Looks up the ruuvitag whose bluez `Name` or `Alias` (e.g. "Ruuvi 4CAD") equals `name` with
+/// `find_devices_by_name`, resolves its mac address and subscribes to it with
+/// `subscribe_ruuvitag`. The lookup scans for 10 seconds on the blocking thread pool of tokio,
+/// so it doesn't hold up the other tasks.
+///
+/// # Errors
+///
+/// If no device or more than one device matches the name.
+///
+/// # Examples
+///
+/// ```
+/// let mut rx = subscribe_by_name("Ruuvi 4CAD").await?;
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     current_sensor_data.print_sensor_data();
+/// }
+/// ```
+pub async fn subscribe_by_name(name: &str) -> Result<Receiver<SensorDataV5>, RuuviError> {
+    let owned_name = name.to_string();
+    let macs = blocking(move || find_devices_by_name(&owned_name)).await?;
+    match macs.as_slice() {
+        [mac] => subscribe_ruuvitag(mac).await,
+        [] => Err(RuuviError::DeviceNotFound(name.to_string())),
+        _ => Err(RuuviError::AmbiguousName {
+            name: name.to_string(),
+            macs,
+        }),
+    }
+}
+
+/// Runs the blocking bluez call `f`, e.g. a discovery, with `tokio::task::spawn_blocking`.
+async fn blocking<T, F>(f: F) -> Result<T, RuuviError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, RuuviError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(io::Error::from)?
+}
+
+/// Subscribe to several ruuvitags and returns a mpsc channel that sends their readings tagged
+/// with the mac address of the tag.
+///
+/// Unlike calling `subscribe_ruuvitag` for every tag, all of the tags share a single D-Bus
+/// connection. A signal match is registered for every tag and each tag is processed by a task of
+/// its own. The readings are tagged with the canonical uppercase form of the mac address.
+///
+/// Only data format 5 readings are sent. Use `subscribe_ruuvitags_with_options` to receive the
+/// readings of the other data formats as well.
+///
+/// # Delivery ordering
+///
+/// Readings of a tag are sent in the order bluez emitted them. Across tags there is no global
+/// order: every tag queues its readings in a channel of its own holding up to
+/// `TAG_CHANNEL_CAPACITY` readings, and the queues are drained into the returned channel
+/// round-robin, one reading per tag at a time. A chatty tag thus gets at most one reading
+/// through before every other tag with a reading waiting has had its turn. Once the queue of a
+/// tag is full, only the readings of that tag are held back.
+///
+/// # Errors
+///
+/// `RuuviError::InvalidMac` if one of `mac_addresses` is malformed, before connecting to D-Bus.
+///
+/// # Stopping
+///
+/// Dropping the receiver stops the subscriptions of all of the tags and closes the D-Bus
+/// connection.
+///
+/// # Examples
+///
+/// ```
+/// // Replace with your mac addresses.
+/// let macs = ["C0:CB:4E:3D:3E:12", "E1:16:22:5D:F6:C9", "CC:6F:70:EE:4C:AD"];
+/// let mut rx = subscribe_ruuvitags(&macs).await?;
+/// while let Some((mac, current_sensor_data)) = rx.recv().await {
+///     println!("{mac}: {}", current_sensor_data.summary());
+/// }
+/// ```
+pub async fn subscribe_ruuvitags(
+    mac_addresses: &[&str],
+) -> Result<Receiver<(String, SensorDataV5)>, RuuviError> {
+    subscribe_tags(
+        MultiSubscribeOptions::new(mac_addresses.iter().copied()),
+        SensorDataV5::try_from,
+    )
+    .await
+}
+
+/// Options of a subscription to several ruuvitags, see `subscribe_ruuvitags_with_options`.
+///
+/// Constructed with the mac addresses of the tags, the rest of the options default to the
+/// behavior of `subscribe_ruuvitags` and can be changed with the builder methods.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::{subscribe_ruuvitags_with_options, MultiSubscribeOptions};
+///
+/// // Replace with your mac addresses.
+/// let options = MultiSubscribeOptions::new(["C0:CB:4E:3D:3E:12", "CC:6F:70:EE:4C:AD"])
+///     .adapters(["hci0", "hci1"])
+///     .expected_format("C0:CB:4E:3D:3E:12".parse()?, 3)
+///     .capacity(64);
+/// let mut rx = subscribe_ruuvitags_with_options(options).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiSubscribeOptions {
+    mac_addresses: Vec<String>,
+    adapters: Vec<String>,
+    expected_formats: HashMap<MacAddress, u8>,
+    capacity: usize,
+    errors: Option<Sender<RuuviError>>,
+}
+
+impl Default for MultiSubscribeOptions {
+    /// Constructs the default options of a subscription without any mac addresses, e.g. for
+    /// `subscribe_all_ruuvitags_with_options`.
+    fn default() -> Self {
+        Self::new(Vec::<String>::new())
+    }
+}
+
+impl MultiSubscribeOptions {
+    /// Constructs the default options of a subscription to the ruuvitags `mac_addresses`.
+    pub fn new<I>(mac_addresses: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            mac_addresses: mac_addresses.into_iter().map(Into::into).collect(),
+            adapters: vec![DEFAULT_ADAPTER.to_string()],
+            expected_formats: HashMap::new(),
+            capacity: DEFAULT_CHANNEL_CAPACITY,
+            errors: None,
+        }
+    }
+
+    /// Listens to the ruuvitags with every adapter of `adapters`, e.g. `["hci0", "hci1"]`,
+    /// instead of only `hci0`.
+    ///
+    /// Every adapter is powered on and scans for the tags. A reading heard by several adapters is
+    /// sent once per adapter, and its data format 5 reading tells which adapter received it and
+    /// with which RSSI, see `SensorDataV5::adapter` and `SensorDataV5::rssi`. An empty list
+    /// keeps `DEFAULT_ADAPTER`.
+    pub fn adapters<I>(mut self, adapters: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let adapters: Vec<String> = adapters.into_iter().map(Into::into).collect();
+        if !adapters.is_empty() {
+            self.adapters = adapters;
+        }
+        self
+    }
+
+    /// Expects the ruuvitag `mac_address` to send data format `data_format`, e.g. `5`.
+    ///
+    /// Readings of any other data format are not sent, but reported to `errors` as
+    /// `RuuviError::FormatMismatch`, which catches tags with misconfigured firmware. The data
+    /// format of the tags without an expected format is detected from their payloads.
+    pub fn expected_format(mut self, mac_address: MacAddress, data_format: u8) -> Self {
+        self.expected_formats.insert(mac_address, data_format);
+        self
+    }
+
+    /// Sets the expected data formats of the ruuvitags, see `expected_format`.
+    pub fn expected_formats(mut self, expected_formats: HashMap<MacAddress, u8>) -> Self {
+        self.expected_formats = expected_formats;
+        self
+    }
+
+    /// Sets the number of readings the channel holds.
+    ///
+    /// # Panics
+    ///
+    /// Subscribing panics if `capacity` is zero.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sends the errors of the subscription to `errors`.
+    ///
+    /// The errors are the ones of signals that couldn't be decoded and readings rejected by
+    /// `expected_format`. They are sent without waiting, so errors are discarded while the
+    /// channel is full rather than holding back the readings.
+    pub fn errors(mut self, errors: Sender<RuuviError>) -> Self {
+        self.errors = Some(errors);
+        self
+    }
+}
+
+/// Subscribe to several ruuvitags configured by `options` and returns a mpsc channel that sends
+/// their readings of any supported data format tagged with the mac address of the tag.
+///
+/// Works like `subscribe_ruuvitags`, which is a shorthand of this function sending only the data
+/// format 5 readings.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::{subscribe_ruuvitags_with_options, MultiSubscribeOptions};
+///
+/// // Replace with your mac addresses.
+/// let options = MultiSubscribeOptions::new(["C0:CB:4E:3D:3E:12", "CC:6F:70:EE:4C:AD"]);
+/// let mut rx = subscribe_ruuvitags_with_options(options).await?;
+/// while let Some((mac, ruuvi_data)) = rx.recv().await {
+///     println!("{mac}: {} °C", ruuvi_data.temperature_in_celcius());
+/// }
+/// ```
+pub async fn subscribe_ruuvitags_with_options(
+    options: MultiSubscribeOptions,
+) -> Result<Receiver<(String, RuuviData)>, RuuviError> {
+    subscribe_tags(options, Ok).await
+}
+
+/// Subscribes to the ruuvitags of `options` on a shared D-Bus connection.
+///
+/// Every decoded reading is passed to `wrap`, and the result is sent to the channel. Readings
+/// `wrap` rejects are reported to the errors of `options`.
+async fn subscribe_tags<T, F>(
+    options: MultiSubscribeOptions,
+    wrap: F,
+) -> Result<Receiver<(String, T)>, RuuviError>
+where
+    T: Send + 'static,
+    F: Fn(RuuviData) -> Result<T, RuuviError> + Send + Sync + 'static,
+{
+    let MultiSubscribeOptions {
+        mac_addresses,
+        adapters,
+        expected_formats,
+        capacity,
+        errors,
+    } = options;
+    let macs = mac_addresses
+        .iter()
+        .map(|mac_address| mac_address.parse())
+        .collect::<Result<Vec<MacAddress>, _>>()?;
+    let (tx, rx) = channel(capacity);
+    let (conn, mut resource_task) = connect_adapters(&adapters).await?;
+    let watcher = TagWatcher {
+        conn,
+        adapters,
+        expected_formats,
+        errors,
+        wrap: Arc::new(wrap),
+    };
+    let mut tasks = JoinSet::new();
+    let mut readings = RoundRobin::new();
+    for mac in macs {
+        match watcher.watch(mac, &mut tasks).await {
+            Ok(tag_rx) => readings.push(tag_rx),
+            Err(e) => {
+                tasks.abort_all();
+                resource_task.abort();
+                return Err(e.into());
+            }
+        }
+    }
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                reading = readings.recv() => match reading {
+                    Some(reading) => if tx.send(reading).await.is_err() {
+                        break;
+                    },
+                    None => break,
+                },
+                _ = tx.closed() => break,
+                // Without a connection the tasks would wait for signals forever.
+                _ = &mut resource_task => {
+                    tasks.abort_all();
+                    return;
+                }
+            }
+        }
+        // Closing the channels of the tags stops their tasks.
+        drop(readings);
+        while tasks.join_next().await.is_some() {}
+        resource_task.abort();
+    });
+    Ok(rx)
+}
+
+/// Subscribe to every ruuvitag nearby and returns a mpsc channel that sends their readings tagged
+/// with the mac address of the tag.
+///
+/// Discovers the tags with `discover_ruuvitags` scanning for `scan`, on the blocking thread pool
+/// of tokio, and subscribes to them like `subscribe_ruuvitags`. The subscription keeps listening
+/// to the bluez `InterfacesAdded` signals, so ruuvitags appearing after the initial scan are
+/// picked up automatically.
+///
+/// # Stopping
+///
+/// Dropping the receiver stops the subscriptions of all of the tags and closes the D-Bus
+/// connection.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let mut rx = subscribe_all_ruuvitags(Duration::from_secs(10)).await?;
+/// while let Some((mac, current_sensor_data)) = rx.recv().await {
+///     println!("{mac}: {}", current_sensor_data.summary());
+/// }
+/// ```
+pub async fn subscribe_all_ruuvitags(
+    scan: Duration,
+) -> Result<Receiver<(String, SensorDataV5)>, RuuviError> {
+    subscribe_all_tags(
+        scan,
+        MultiSubscribeOptions::default(),
+        SensorDataV5::try_from,
+    )
+    .await
+}
+
+/// Subscribe to every ruuvitag nearby configured by `options` and returns a mpsc channel that
+/// sends their readings of any supported data format tagged with the mac address of the tag.
+///
+/// Works like `subscribe_all_ruuvitags`, but scans with the adapters of `options` through
+/// `discover_ruuvitags_with_adapters` and subscribes to the mac addresses of `options` in
+/// addition to the discovered ones.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::{subscribe_all_ruuvitags_with_options, MultiSubscribeOptions};
+/// use std::time::Duration;
+///
+/// let options = MultiSubscribeOptions::default().adapters(["hci0", "hci1"]);
+/// let mut rx = subscribe_all_ruuvitags_with_options(Duration::from_secs(10), options).await?;
+/// while let Some((mac, ruuvi_data)) = rx.recv().await {
+///     if let RuuviData::V5(sensor_data) = ruuvi_data {
+///         println!("{mac} via {:?}: {:?} dBm", sensor_data.adapter(), sensor_data.rssi());
+///     }
+/// }
+/// ```
+pub async fn subscribe_all_ruuvitags_with_options(
+    scan: Duration,
+    options: MultiSubscribeOptions,
+) -> Result<Receiver<(String, RuuviData)>, RuuviError> {
+    subscribe_all_tags(scan, options, Ok).await
+}
+
+/// Discovers the ruuvitags nearby for `scan` and subscribes to them and the ruuvitags of
+/// `options`, like `subscribe_tags`, and to the ruuvitags appearing later on.
+async fn subscribe_all_tags<T, F>(
+    scan: Duration,
+    options: MultiSubscribeOptions,
+    wrap: F,
+) -> Result<Receiver<(String, T)>, RuuviError>
+where
+    T: Send + 'static,
+    F: Fn(RuuviData) -> Result<T, RuuviError> + Send + Sync + 'static,
+{
+    let MultiSubscribeOptions {
+        mac_addresses,
+        adapters,
+        expected_formats,
+        capacity,
+        errors,
+    } = options;
+    let mut macs = mac_addresses
+        .iter()
+        .map(|mac_address| mac_address.parse())
+        .collect::<Result<Vec<MacAddress>, _>>()?;
+    let (tx, rx) = channel(capacity);
+    // Connecting first keeps every adapter discovering during the scan. The scan blocks, so it
+    // runs on the blocking thread pool instead of holding up the executor.
+    let (conn, mut resource_task) = connect_adapters(&adapters).await?;
+    let scan_adapters = adapters.clone();
+    let scanned = blocking(move || {
+        let scan_adapters: Vec<&str> = scan_adapters.iter().map(String::as_str).collect();
+        discover_ruuvitags_with_adapters(scan, &scan_adapters)
+    });
+    match scanned.await {
+        Ok(discovered) => macs.extend(
+            discovered
+                .iter()
+                .filter_map(|mac_address| mac_address.parse::<MacAddress>().ok()),
+        ),
+        Err(e) => {
+            resource_task.abort();
+            return Err(e);
+        }
+    }
+    let watcher = TagWatcher {
+        conn,
+        adapters,
+        expected_formats,
+        errors,
+        wrap: Arc::new(wrap),
+    };
+    let mut tasks = JoinSet::new();
+    let mut readings = RoundRobin::new();
+    let rule =
+        ObjectManagerInterfacesAdded::match_rule(Some(&"org.bluez".into()), Some(&"/".into()))
+            .static_clone();
+    let (added_match, mut added_signals) = match watcher.conn.add_match(rule).await {
+        Ok(added_match) => added_match.stream::<ObjectManagerInterfacesAdded>(),
+        Err(e) => {
+            resource_task.abort();
+            return Err(e.into());
+        }
+    };
+    let mut watched = HashSet::new();
+    for mac in macs {
+        if !watched.insert(mac) {
+            continue;
+        }
+        match watcher.watch(mac, &mut tasks).await {
+            Ok(tag_rx) => readings.push(tag_rx),
+            Err(e) => {
+                tasks.abort_all();
+                resource_task.abort();
+                return Err(e.into());
+            }
+        }
+    }
+    tokio::spawn(async move {
+        loop {
+            let added = tokio::select! {
+                // Until a tag is found there is nothing to receive.
+                reading = readings.recv(), if !readings.is_empty() => {
+                    if let Some(reading) = reading {
+                        if tx.send(reading).await.is_err() {
+                            break;
+                        }
+                    }
+                    continue;
+                },
+                signal = added_signals.next() => match signal {
+                    Some((_, added)) => added,
+                    None => break,
+                },
+                _ = tx.closed() => break,
+                // Without a connection the tasks would wait for signals forever.
+                _ = &mut resource_task => {
+                    tasks.abort_all();
+                    return;
+                }
+            };
+            let Some(mac) = watcher
+                .adapters
+                .iter()
+                .find_map(|adapter| added_ruuvitag(adapter, &added))
+            else {
+                continue;
+            };
+            if !watched.insert(mac) {
+                continue;
+            }
+            match watcher.watch(mac, &mut tasks).await {
+                Ok(tag_rx) => readings.push(tag_rx),
+                Err(_) => {
+                    watched.remove(&mac);
+                }
+            }
+        }
+        // Closing the channels of the tags stops their tasks.
+        drop(readings);
+        while tasks.join_next().await.is_some() {}
+        let _ = watcher.conn.remove_match(added_match.token()).await;
+        resource_task.abort();
+    });
+    Ok(rx)
+}
+
+/// Returns the mac address of the device added by `added` if it is a ruuvitag of `adapter`.
+fn added_ruuvitag(adapter: &str, added: &ObjectManagerInterfacesAdded) -> Option<MacAddress> {
+    if !added
+        .object
+        .starts_with(&format!("{}/", adapter_path(adapter)))
+    {
+        return None;
+    }
+    let properties = added.interfaces.get("org.bluez.Device1")?;
+    if !has_ruuvi_manufacturer_data(properties) {
+        return None;
+    }
+    arg::prop_cast::<String>(properties, "Address")?
+        .parse()
+        .ok()
+}
+
+/// Connects to D-Bus, powers on every adapter of `adapters` and starts discovery on them.
+async fn connect_adapters(
+    adapters: &[String],
+) -> Result<(Arc<SyncConnection>, JoinHandle<()>), RuuviError> {
+    let mut adapters = adapters.iter();
+    let mut connect = ConnectOptions::default();
+    if let Some(adapter) = adapters.next() {
+        connect.adapter = adapter.clone();
+    }
+    let (conn, resource_task) = connect_bluetooth_async(&connect).await?;
+    for adapter in adapters {
+        connect.adapter = adapter.clone();
+        if let Err(e) = power_on_adapter_async(&conn, &adapter_path(adapter), &connect).await {
+            resource_task.abort();
+            return Err(e.into());
+        }
+    }
+    Ok((conn, resource_task))
+}
+
+/// Shared state of the tasks of a subscription to several ruuvitags.
+struct TagWatcher<F> {
+    conn: Arc<SyncConnection>,
+    adapters: Vec<String>,
+    expected_formats: HashMap<MacAddress, u8>,
+    errors: Option<Sender<RuuviError>>,
+    wrap: Arc<F>,
+}
+
+impl<F> TagWatcher<F> {
+    /// Registers a signal match for the ruuvitag `mac` on every adapter and spawns a task per
+    /// adapter to `tasks` forwarding its readings to the returned channel until the channel is
+    /// closed.
+    async fn watch<T>(
+        &self,
+        mac: MacAddress,
+        tasks: &mut JoinSet<()>,
+    ) -> Result<Receiver<(String, T)>, dbus::Error>
+    where
+        T: Send + 'static,
+        F: Fn(RuuviData) -> Result<T, RuuviError> + Send + Sync + 'static,
+    {
+        let (tx, rx) = channel(TAG_CHANNEL_CAPACITY);
+        for adapter in &self.adapters {
+            let rule = PropertiesPropertiesChanged::match_rule(
+                Some(&"org.bluez".into()),
+                Some(&mac.device_path(adapter).into()),
+            )
+            .static_clone();
+            let (signal_match, mut signals) = self
+                .conn
+                .add_match(rule)
+                .await?
+                .stream::<PropertiesPropertiesChanged>();
+            let conn = Arc::clone(&self.conn);
+            let mut decoder = TagDecoder::new(
+                mac,
+                adapter.clone(),
+                self.expected_formats.get(&mac).copied(),
+            );
+            let errors = self.errors.clone();
+            let wrap = Arc::clone(&self.wrap);
+            let mac_address = mac.to_string();
+            let tx = tx.clone();
+            tasks.spawn(async move {
+                loop {
+                    let h = tokio::select! {
+                        signal = signals.next() => match signal {
+                            Some((_, h)) => h,
+                            None => break,
+                        },
+                        _ = tx.closed() => break,
+                    };
+                    let reading = match decoder.decode(h.changed_properties) {
+                        Some(reading) => reading.and_then(|ruuvi_data| wrap(ruuvi_data)),
+                        None => continue,
+                    };
+                    match reading {
+                        Ok(reading) => {
+                            if tx.send((mac_address.clone(), reading)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => report(&errors, e),
+                    }
+                }
+                let _ = conn.remove_match(signal_match.token()).await;
+            });
+        }
+        Ok(rx)
+    }
+}
+
+/// Decodes the signals of a ruuvitag received by one bluetooth adapter.
+#[derive(Debug)]
+struct TagDecoder {
+    mac: MacAddress,
+    adapter: String,
+    expected_format: Option<u8>,
+    /// RSSI of the latest signal carrying one. Bluez signals a changed RSSI without the
+    /// manufacturer data if the data didn't change.
+    rssi: Option<i16>,
+}
+
+impl TagDecoder {
+    fn new(mac: MacAddress, adapter: String, expected_format: Option<u8>) -> Self {
+        Self {
+            mac,
+            adapter,
+            expected_format,
+            rssi: None,
+        }
+    }
+
+    /// Decodes the reading in the changed properties of a `PropertiesChanged` signal.
+    ///
+    /// Returns `None` if the signal carries no reading, e.g. updates of only the RSSI. Readings
+    /// are either manufacturer data or, of a tag in URL mode, Eddystone service data, see
+    /// `RuuviData::from_dbus_changed_properties`. The data format is detected from the payload,
+    /// and a reading of another data format than the expected one, if any, is rejected with
+    /// `RuuviError::FormatMismatch`. Data format 5 readings are tagged with the adapter and the
+    /// latest RSSI.
+    fn decode(
+        &mut self,
+        changed_properties: arg::PropMap,
+    ) -> Option<Result<RuuviData, RuuviError>> {
+        if let Some(rssi) = rssi(&changed_properties) {
+            self.rssi = Some(rssi);
+        }
+        if !changed_properties.contains_key("ManufacturerData")
+            && !changed_properties.contains_key("ServiceData")
+        {
+            return None;
+        }
+        let mut ruuvi_data = match RuuviData::from_dbus_changed_properties(changed_properties) {
+            Ok(ruuvi_data) => ruuvi_data,
+            Err(e) => return Some(Err(e)),
+        };
+        let data_format = ruuvi_data.data_format();
+        if let Some(expected) = self
+            .expected_format
+            .filter(|&expected| expected != data_format)
+        {
+            return Some(Err(RuuviError::FormatMismatch {
+                mac: self.mac.to_string(),
+                expected,
+                got: data_format,
+            }));
+        }
+        if let RuuviData::V5(sensor_data) = &mut ruuvi_data {
+            sensor_data.adapter = Some(self.adapter.clone());
+            sensor_data.rssi = self.rssi;
+        }
+        Some(Ok(ruuvi_data))
+    }
+}
+
+/// Receives from the channels of the ruuvitags of a subscription in turns.
+///
+/// Every `recv` starts from the channel after the one received from last, so a tag with a
+/// backlog of readings gets one reading through per round like every other tag.
+struct RoundRobin<T> {
+    receivers: Vec<Receiver<T>>,
+    next: usize,
+}
+
+impl<T> RoundRobin<T> {
+    fn new() -> Self {
+        Self {
+            receivers: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Adds the channel `rx` to the rotation.
+    fn push(&mut self, rx: Receiver<T>) {
+        self.receivers.push(rx);
+    }
+
+    /// Returns `true` if there are no open channels left.
+    fn is_empty(&self) -> bool {
+        self.receivers.is_empty()
+    }
+
+    /// Receives the next reading. Returns `None` once all of the channels are closed and empty.
+    async fn recv(&mut self) -> Option<T> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut index = self.next;
+        let mut polled = 0;
+        while polled < self.receivers.len() {
+            index %= self.receivers.len();
+            match self.receivers[index].poll_recv(cx) {
+                Poll::Ready(Some(reading)) => {
+                    self.next = index + 1;
+                    return Poll::Ready(Some(reading));
+                }
+                // The task of the tag has stopped.
+                Poll::Ready(None) => {
+                    self.receivers.remove(index);
+                }
+                Poll::Pending => {
+                    index += 1;
+                    polled += 1;
+                }
+            }
+        }
+        if self.receivers.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Decodes the Ruuvi manufacturer data payload of any supported data format.
+///
+/// The data format is detected from the first byte of the payload. Supports data formats 3
+/// (RAWv1) and 5 (RAWv2).
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::{decode, RuuviData};
+///
+/// let manufacturer_data = [
+///     0x03, 0x29, 0x1A, 0x1E, 0xCE, 0x1E, 0xFC, 0x18, 0xF9, 0x42, 0x02, 0xCA, 0x0B, 0x53,
+/// ];
+/// match decode(&manufacturer_data).unwrap() {
+///     RuuviData::V3(sensor_data) => println!("{}", sensor_data.temperature_in_celcius()),
+///     RuuviData::V5(sensor_data) => println!("{}", sensor_data.temperature_in_celcius()),
+///     other => println!("{other:?}"),
+/// }
+/// ```
+pub fn decode(manufacturer_data: &[u8]) -> Result<RuuviData, RuuviError> {
+    match manufacturer_data.first() {
+        Some(&DATA_FORMAT_V3) => SensorDataV3::from_raw_bytes(manufacturer_data).map(RuuviData::V3),
+        Some(&DATA_FORMAT_V5) => SensorDataV5::from_raw_bytes(manufacturer_data).map(RuuviData::V5),
+        Some(data_format) => Err(RuuviError::UnsupportedFormat(*data_format)),
+        None => Err(RuuviError::Malformed("Empty manufacturer data".to_string())),
+    }
+}
+
+/// Ruuvitag data of any supported data format.
+#[derive(Debug, Clone)]
+pub enum RuuviData {
+    /// Data formats 2 and 4 (URL).
+    V2(SensorDataV2),
+    /// Data format 3 (RAWv1).
+    V3(SensorDataV3),
+    /// Data format 5 (RAWv2).
+    V5(SensorDataV5),
+}
+
+impl RuuviData {
+    /// Constructs a `RuuviData` from dbus message `PropertiesChanged`.
+    ///
+    /// Decodes the `ManufacturerData` with [`decode`] if present, otherwise the Eddystone-URL
+    /// `ServiceData` of a ruuvitag in URL mode.
+    pub fn from_dbus_changed_properties(
+        changed_properties: arg::PropMap,
+    ) -> Result<Self, RuuviError> {
+        if !changed_properties.contains_key("ManufacturerData") {
+            return SensorDataV2::from_dbus_changed_properties(changed_properties)
+                .map(RuuviData::V2);
+        }
+        let temp = ruuvi_manufacturer_data(&changed_properties)?;
+        match decode(&temp)? {
+            RuuviData::V5(mut sensor_data) => {
+                sensor_data.rssi = rssi(&changed_properties);
+                Ok(RuuviData::V5(sensor_data))
+            }
+            ruuvi_data => Ok(ruuvi_data),
+        }
+    }
+
+    /// Returns the data format of the data.
+    pub fn data_format(&self) -> u8 {
+        match self {
+            RuuviData::V2(sensor_data) => sensor_data.data_format(),
+            RuuviData::V3(sensor_data) => sensor_data.data_format(),
+            RuuviData::V5(sensor_data) => sensor_data.data_format(),
+        }
+    }
+
+    /// Returns temperature in celcius.
+    pub fn temperature_in_celcius(&self) -> f64 {
+        match self {
+            RuuviData::V2(sensor_data) => sensor_data.temperature_in_celcius(),
+            RuuviData::V3(sensor_data) => sensor_data.temperature_in_celcius(),
+            RuuviData::V5(sensor_data) => sensor_data.temperature_in_celcius(),
+        }
+    }
+
+    /// Returns humidity in %.
+    pub fn get_humidity(&self) -> f64 {
+        match self {
+            RuuviData::V2(sensor_data) => sensor_data.get_humidity(),
+            RuuviData::V3(sensor_data) => sensor_data.get_humidity(),
+            RuuviData::V5(sensor_data) => sensor_data.get_humidity(),
+        }
+    }
+
+    /// Returns pressure in Pa.
+    pub fn get_pressure(&self) -> u32 {
+        match self {
+            RuuviData::V2(sensor_data) => sensor_data.get_pressure(),
+            RuuviData::V3(sensor_data) => sensor_data.get_pressure(),
+            RuuviData::V5(sensor_data) => sensor_data.get_pressure(),
+        }
+    }
+}
+
+impl TryFrom<RuuviData> for SensorDataV5 {
+    type Error = RuuviError;
+
+    /// Returns the data format 5 reading of `ruuvi_data`, or `RuuviError::UnsupportedFormat` of
+    /// the other data formats.
+    fn try_from(ruuvi_data: RuuviData) -> Result<Self, RuuviError> {
+        match ruuvi_data {
+            RuuviData::V5(sensor_data) => Ok(sensor_data),
+            ruuvi_data => Err(RuuviError::UnsupportedFormat(ruuvi_data.data_format())),
+        }
+    }
+}
+
+/// A structure to hold ruuvitag data from V5 format.
+///
+/// Implementation following ruuvi data format 5
+/// <https://github.com/ruuvi/ruuvi-sensor-protocols/blob/master/dataformat_05.md>
+///
+/// Values the ruuvitag reports as not available keep their raw sentinel, e.g. `0x8000` for the
+/// temperature. The `try_*` accessors return `None` for them.
+///
+/// Readings are equal and hash the same when their payloads are equal, the adapter and RSSI
+/// they were received with are ignored. The same advertisement heard by two adapters is one
+/// reading.
+#[derive(Debug, Clone)]
+pub struct SensorDataV5 {
+    temperature: i16,
+    humidity: u16,
+    pressure: u16,
+    acceleration: Acceleration,
+    power_info: u16,
+    movement_counter: u8,
+    measurement_number: u16,
+    mac: [u8; 6],
+    adapter: Option<String>,
+    rssi: Option<i16>,
+}
+
+impl SensorDataV5 {
+    /// Constructs a new `SensorDataV5`.
+    pub fn new(
+        temperature: i16,
+        humidity: u16,
+        pressure: u16,
+        acceleration: Acceleration,
+        power_info: u16,
+        movement_counter: u8,
+        measurement_number: u16,
+        mac: [u8; 6],
+    ) -> Self {
+        Self {
+            temperature,
+            humidity,
+            pressure,
+            acceleration,
+            power_info,
+            movement_counter,
+            measurement_number,
+            mac,
+            adapter: None,
+            rssi: None,
+        }
+    }
+
+    /// Constructs a `SensorDataV5` from dbus message `PropertiesChanged`.
+    pub fn from_dbus_changed_properties(
+        changed_properties: arg::PropMap,
+    ) -> Result<Self, RuuviError> {
+        let temp = ruuvi_manufacturer_data(&changed_properties)?;
+        let mut sensor_data = SensorDataV5::from_raw_bytes(&temp)?;
+        sensor_data.rssi = rssi(&changed_properties);
+        Ok(sensor_data)
+    }
+
+    /// Constructs a `SensorDataV5` from a full bluetooth advertisement.
+    ///
+    /// Walks the AD structures (length, type, data) of the advertisement, locates the
+    /// manufacturer specific data (type `0xFF`) of Ruuvi Innovations (company ID `0x0499`) and
+    /// decodes the payload following the company ID. This handles raw HCI advertisement captures
+    /// which include the AD structure headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ruuviscanner::ruuvitag::SensorDataV5;
+    ///
+    /// let advertisement = [
+    ///     0x02, 0x01, 0x06, 0x1B, 0xFF, 0x99, 0x04, 0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C,
+    ///     0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC, 0x36, 0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33,
+    ///     0x4C, 0x88, 0x4F,
+    /// ];
+    /// let sensor_data = SensorDataV5::from_advertisement_bytes(&advertisement).unwrap();
+    /// assert_eq!(sensor_data.mac_as_str(), "CB:B8:33:4C:88:4F");
+    /// ```
+    pub fn from_advertisement_bytes(advertisement: &[u8]) -> Result<Self, RuuviError> {
+        let mut remaining = advertisement;
+        let mut found = Vec::new();
+        while let Some((&length, rest)) = remaining.split_first() {
+            let length = length as usize;
+            // Zero length marks the end of significant data, the rest is padding.
+            if length == 0 {
+                break;
+            }
+            if length > rest.len() {
+                return Err(RuuviError::Malformed(format!(
+                    "AD structure length {length} exceeds the remaining {} bytes",
+                    rest.len()
+                )));
+            }
+            let (ad_structure, rest) = rest.split_at(length);
+            remaining = rest;
+            let (ad_type, ad_data) = (ad_structure[0], &ad_structure[1..]);
+            if ad_type != AD_TYPE_MANUFACTURER_DATA {
+                continue;
+            }
+            if ad_data.len() < 2 {
+                return Err(RuuviError::Malformed(format!(
+                    "Manufacturer data is missing company ID {ad_data:?}"
+                )));
+            }
+            let company_id = u16::from_le_bytes([ad_data[0], ad_data[1]]);
+            if company_id == RUUVI_MANUFACTURER_ID {
+                return SensorDataV5::from_raw_bytes(&ad_data[2..]);
+            }
+            found.push(format!("{company_id:#06X}"));
+        }
+        Err(RuuviError::MissingManufacturerData { found })
+    }
+
+    /// Constructs a `SensorDataV5` from the 24 byte manufacturer data payload.
+    ///
+    /// The payload is the manufacturer data following the company ID, e.g. as captured from an
+    /// advertisement. Useful for replaying captures and testing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ruuviscanner::ruuvitag::SensorDataV5;
+    ///
+    /// let payload = [
+    ///     0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC,
+    ///     0x36, 0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F,
+    /// ];
+    /// let sensor_data = SensorDataV5::from_raw_bytes(&payload).unwrap();
+    /// assert_eq!(sensor_data.temperature_in_celcius(), 24.3);
+    /// ```
+    pub fn from_raw_bytes(temp: &[u8]) -> Result<Self, RuuviError> {
+        if temp.len() != 24 {
+            return Err(RuuviError::WrongLength {
+                expected: 24,
+                got: temp.len(),
+            });
+        }
+        if temp[0] != DATA_FORMAT_V5 {
+            return Err(RuuviError::UnsupportedFormat(temp[0]));
+        }
+        let temperature = join_u8(temp[1], temp[2]) as i16;
+        let humidity = join_u8(temp[3], temp[4]);
+        let pressure = join_u8(temp[5], temp[6]);
+        let acceleration = Acceleration {
+            x: join_u8(temp[7], temp[8]) as i16,
+            y: join_u8(temp[9], temp[10]) as i16,
+            z: join_u8(temp[11], temp[12]) as i16,
+        };
+        let power_info = join_u8(temp[13], temp[14]);
+        let movement_counter = temp[15];
+        let measurement_number = join_u8(temp[16], temp[17]);
+        let mac: [u8; 6] = [temp[18], temp[19], temp[20], temp[21], temp[22], temp[23]];
+
+        Ok(SensorDataV5::new(
+            temperature,
+            humidity,
+            pressure,
+            acceleration,
+            power_info,
+            movement_counter,
+            measurement_number,
+            mac,
+        ))
+    }
+
+    /// Returns the sensor data encoded as the 24 byte data format 5 payload.
+    ///
+    /// The inverse of decoding the manufacturer data of a ruuvitag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:02X?}", current_sensor_data.to_raw_bytes());
+    /// }
+    /// ```
+    pub fn to_raw_bytes(&self) -> [u8; 24] {
+        let mut payload = [0_u8; 24];
+        payload[0] = DATA_FORMAT_V5;
+        payload[1..3].copy_from_slice(&self.temperature.to_be_bytes());
+        payload[3..5].copy_from_slice(&self.humidity.to_be_bytes());
+        payload[5..7].copy_from_slice(&self.pressure.to_be_bytes());
+        payload[7..9].copy_from_slice(&self.acceleration.x.to_be_bytes());
+        payload[9..11].copy_from_slice(&self.acceleration.y.to_be_bytes());
+        payload[11..13].copy_from_slice(&self.acceleration.z.to_be_bytes());
+        payload[13..15].copy_from_slice(&self.power_info.to_be_bytes());
+        payload[15] = self.movement_counter;
+        payload[16..18].copy_from_slice(&self.measurement_number.to_be_bytes());
+        payload[18..24].copy_from_slice(&self.mac);
+        payload
+    }
+
+    /// Returns `false` if the ruuvitag reported `field` as invalid / not available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.is_valid(SensorField::Temperature));
+    /// }
+    /// ```
+    pub fn is_valid(&self, field: SensorField) -> bool {
+        match field {
+            SensorField::Temperature => self.temperature_is_valid(),
+            SensorField::Humidity => self.humidity_is_valid(),
+            SensorField::Pressure => self.pressure_is_valid(),
+            SensorField::Acceleration => self.acceleration_is_valid(),
+        }
+    }
+
+    /// Returns `false` if the temperature is reported as not available (raw value `0x8000`).
+    pub fn temperature_is_valid(&self) -> bool {
+        self.temperature != INVALID_TEMPERATURE
+    }
+
+    /// Returns `false` if the humidity is reported as not available (raw value `0xFFFF`).
+    pub fn humidity_is_valid(&self) -> bool {
+        self.humidity != INVALID_HUMIDITY
+    }
+
+    /// Returns `false` if the pressure is reported as not available (raw value `0xFFFF`).
+    pub fn pressure_is_valid(&self) -> bool {
+        self.pressure != INVALID_PRESSURE
+    }
+
+    /// Returns `false` if any axis of the acceleration is reported as not available (raw value
+    /// `0x8000`).
+    pub fn acceleration_is_valid(&self) -> bool {
+        [
+            self.acceleration.x,
+            self.acceleration.y,
+            self.acceleration.z,
+        ]
+        .iter()
+        .all(|axis| *axis != INVALID_ACCELERATION)
+    }
+
+    /// Returns `true` if the temperature, humidity and pressure are within the operating ranges
+    /// of the sensor.
+    ///
+    /// The ranges are -40...+85°C, 0...100% and 300...1100hPa. Fields reported as not available
+    /// are not considered implausible.
+    pub fn is_plausible(&self) -> bool {
+        (!self.temperature_is_valid()
+            || PLAUSIBLE_TEMPERATURE_CELSIUS.contains(&self.temperature_in_celcius()))
+            && (!self.humidity_is_valid() || PLAUSIBLE_HUMIDITY.contains(&self.get_humidity()))
+            && (!self.pressure_is_valid() || PLAUSIBLE_PRESSURE_PA.contains(&self.get_pressure()))
+    }
+
+    /// Replaces the raw value of `field` with the one in `other`.
+    pub(crate) fn copy_field_from(&mut self, field: SensorField, other: &SensorDataV5) {
+        match field {
+            SensorField::Temperature => self.temperature = other.temperature,
+            SensorField::Humidity => self.humidity = other.humidity,
+            SensorField::Pressure => self.pressure = other.pressure,
+            SensorField::Acceleration => self.acceleration = other.acceleration,
+        }
+    }
+
+    /// Returns the current temperature measured from ruuvitag in `unit`.
+    ///
+    /// Useful when the unit is chosen at runtime, e.g. from the configuration of the user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let unit = TemperatureUnit::Fahrenheit;
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}{}", current_sensor_data.temperature_in(unit), unit.symbol());
+    /// }
+    /// ```
+    pub fn temperature_in(&self, unit: TemperatureUnit) -> f64 {
+        unit.convert_millicelcius(i32::from(self.temperature) * 5)
+    }
+    /// Returns the current temperature measured from ruuvitag in `unit`.
+    ///
+    /// `None` if the ruuvitag reports the temperature as not available (raw value `0x8000`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.try_temperature_in(TemperatureUnit::Kelvin));
+    /// }
+    /// ```
+    pub fn try_temperature_in(&self, unit: TemperatureUnit) -> Option<f64> {
+        if !self.temperature_is_valid() {
+            return None;
+        }
+        Some(self.temperature_in(unit))
+    }
+    /// Returns the current temperature measured from ruuvitag in millicelsius.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.temperature_in_millicelcius());
+    /// }
+    /// ```
+    pub fn temperature_in_millicelcius(&self) -> i32 {
+        self.temperature_in(TemperatureUnit::Millicelsius) as i32
+    }
+    /// Returns the current temperature measured from ruuvitag in celsius.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.temperature_in_celcius());
+    /// }
+    /// ```
+    pub fn temperature_in_celcius(&self) -> f64 {
+        self.temperature_in(TemperatureUnit::Celsius)
+    }
+    /// Returns the current temperature measured from ruuvitag in millicelsius.
+    ///
+    /// `None` if the ruuvitag reports the temperature as not available (raw value `0x8000`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.try_temperature_in_millicelcius());
+    /// }
+    /// ```
+    pub fn try_temperature_in_millicelcius(&self) -> Option<i32> {
+        self.try_temperature_in(TemperatureUnit::Millicelsius)
+            .map(|x| x as i32)
+    }
+    /// Returns the current temperature measured from ruuvitag in celsius.
+    ///
+    /// `None` if the ruuvitag reports the temperature as not available (raw value `0x8000`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.try_temperature_in_celcius());
+    /// }
+    /// ```
+    pub fn try_temperature_in_celcius(&self) -> Option<f64> {
+        self.try_temperature_in(TemperatureUnit::Celsius)
+    }
+    /// Returns the current temperature measured from ruuvitag in fahrenheit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.temperature_in_fahrenheit());
+    /// }
+    /// ```
+    pub fn temperature_in_fahrenheit(&self) -> f64 {
+        self.temperature_in(TemperatureUnit::Fahrenheit)
+    }
+    /// Returns the current temperature measured from ruuvitag in kelvin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.temperature_in_kelvin());
+    /// }
+    /// ```
+    pub fn temperature_in_kelvin(&self) -> f64 {
+        self.temperature_in(TemperatureUnit::Kelvin)
+    }
+    /// Returns the current temperature measured from ruuvitag in fahrenheit.
+    ///
+    /// `None` if the ruuvitag reports the temperature as not available (raw value `0x8000`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.try_temperature_in_fahrenheit());
+    /// }
+    /// ```
+    pub fn try_temperature_in_fahrenheit(&self) -> Option<f64> {
+        self.try_temperature_in(TemperatureUnit::Fahrenheit)
+    }
+    /// Returns the current temperature measured from ruuvitag in kelvin.
+    ///
+    /// `None` if the ruuvitag reports the temperature as not available (raw value `0x8000`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.try_temperature_in_kelvin());
+    /// }
+    /// ```
+    pub fn try_temperature_in_kelvin(&self) -> Option<f64> {
+        self.try_temperature_in(TemperatureUnit::Kelvin)
+    }
+    /// Returns the operative temperature in celsius.
+    ///
+    /// Ruuvitag only measures the air temperature so the mean radiant temperature `radiant_c`
+    /// (°C) and the air speed `air_speed` (m/s) have to be supplied by the caller. Uses the
+    /// ASHRAE 55 formula `t_o = A * t_a + (1 - A) * t_r`, where the weighting factor `A` is 0.5
+    /// below 0.2 m/s, 0.6 below 0.6 m/s and 0.7 otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.operative_temperature(22.0, 0.1));
+    /// }
+    /// ```
+    pub fn operative_temperature(&self, radiant_c: f64, air_speed: f64) -> f64 {
+        let weight = if air_speed < 0.2 {
+            0.5
+        } else if air_speed < 0.6 {
+            0.6
+        } else {
+            0.7
+        };
+        weight * self.temperature_in_celcius() + (1_f64 - weight) * radiant_c
+    }
+    /// Returns the current humidity % measured from ruuvitag.
+    ///
+    /// Due to the tolerance of the sensor the humidity can read slightly above 100% near
+    /// saturation. The raw value is returned as is, use `get_humidity_clamped` to limit it to
+    /// 100%.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.get_humidity());
+    /// }
+    /// ```
+    pub fn get_humidity(&self) -> f64 {
+        self.humidity as f64 / 400_f64
+    }
+    /// Returns the current humidity % measured from ruuvitag clamped to at most 100%.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.get_humidity_clamped());
+    /// }
+    /// ```
+    pub fn get_humidity_clamped(&self) -> f64 {
+        self.get_humidity().min(100_f64)
+    }
+    /// Returns the current humidity % measured from ruuvitag.
+    ///
+    /// `None` if the ruuvitag reports the humidity as not available (raw value `0xFFFF`).
+    pub fn try_get_humidity(&self) -> Option<f64> {
+        self.humidity_is_valid().then(|| self.get_humidity())
+    }
+
+    /// Returns the current air pressure in Pa measured from ruuvitag.
+    ///
+    /// Use `get_pressure_hpa` for hPa, the unit weather services usually report.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.get_pressure());
+    /// }
+    /// ```
+    pub fn get_pressure(&self) -> u32 {
+        50000 + self.pressure as u32
+    }
+
+    /// Returns the current air pressure in hPa measured from ruuvitag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.get_pressure_hpa());
+    /// }
+    /// ```
+    pub fn get_pressure_hpa(&self) -> f64 {
+        self.get_pressure() as f64 / 100_f64
+    }
+
+    /// Returns the current air pressure in Pa measured from ruuvitag.
+    ///
+    /// `None` if the ruuvitag reports the pressure as not available (raw value `0xFFFF`).
+    pub fn try_get_pressure(&self) -> Option<u32> {
+        self.pressure_is_valid().then(|| self.get_pressure())
+    }
+
+    /// Returns the current air pressure in hPa measured from ruuvitag.
+    ///
+    /// `None` if the ruuvitag reports the pressure as not available (raw value `0xFFFF`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.try_get_pressure_hpa());
+    /// }
+    /// ```
+    pub fn try_get_pressure_hpa(&self) -> Option<f64> {
+        self.try_get_pressure()
+            .map(|pressure| pressure as f64 / 100_f64)
+    }
+
+    /// Returns the current air pressure in millimeters of mercury (mmHg) measured from ruuvitag.
+    ///
+    /// `None` if the ruuvitag reports the pressure as not available (raw value `0xFFFF`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.try_get_pressure_mmhg());
+    /// }
+    /// ```
+    pub fn try_get_pressure_mmhg(&self) -> Option<f64> {
+        self.try_get_pressure()
+            .map(|pressure| pressure as f64 / PA_PER_MMHG)
+    }
+
+    /// Returns the current air pressure in inches of mercury (inHg) measured from ruuvitag.
+    ///
+    /// `None` if the ruuvitag reports the pressure as not available (raw value `0xFFFF`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.try_get_pressure_inhg());
+    /// }
+    /// ```
+    pub fn try_get_pressure_inhg(&self) -> Option<f64> {
+        self.try_get_pressure()
+            .map(|pressure| pressure as f64 / PA_PER_INHG)
+    }
+
+    /// Returns an estimate of the altitude in meters from the current air pressure.
+    ///
+    /// Uses the international barometric formula `h = 44330 * (1 - (p / p0)^(1 / 5.255))`,
+    /// where `p0` is `sea_level_pressure_pa`, the current air pressure at sea level. Weather
+    /// services report it as QNH, [`STANDARD_SEA_LEVEL_PRESSURE_PA`] can be used without one
+    /// at the cost of an error of tens of meters as the weather changes.
+    ///
+    /// `None` if the ruuvitag reports the pressure as not available (raw value `0xFFFF`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.altitude_meters(102100));
+    /// }
+    /// ```
+    pub fn altitude_meters(&self, sea_level_pressure_pa: u32) -> Option<f64> {
+        self.try_get_pressure().map(|pressure| {
+            44330_f64
+                * (1_f64 - (pressure as f64 / sea_level_pressure_pa as f64).powf(1_f64 / 5.255))
+        })
+    }
+
+    /// Returns the current `Acceleration` mG measured from ruuvitag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.get_acceleration_in_mg());
+    /// }
+    /// ```
+    pub fn get_acceleration_in_mg(&self) -> &Acceleration {
+        &self.acceleration
+    }
+    /// Returns the current battery voltage (mV) measured from ruuvitag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.get_battery_voltage());
+    /// }
+    /// ```
+    pub fn get_battery_voltage(&self) -> u16 {
+        decode_battery_voltage(self.power_info)
+    }
+
+    /// Returns `true` if the battery voltage is below `threshold_mv`.
+    ///
+    /// [`LOW_BATTERY_THRESHOLD_MV`] is a sensible default. The voltage of a CR2477 battery sags
+    /// in the cold and recovers when it gets warmer, so a tag outdoors may report a low battery
+    /// on a cold night that is fine in the afternoon. Use [`crate::health::BatteryHealth`] to
+    /// tell a depleted battery from a cold one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     if current_sensor_data.battery_is_low(LOW_BATTERY_THRESHOLD_MV) {
+    ///         eprintln!("Replace the battery of {}", current_sensor_data.mac_as_str());
+    ///     }
+    /// }
+    /// ```
+    pub fn battery_is_low(&self, threshold_mv: u16) -> bool {
+        self.get_battery_voltage() < threshold_mv
+    }
+
+    /// Returns an estimate of the remaining battery capacity in %.
+    ///
+    /// Maps the battery voltage to the capacity with a piecewise linear approximation of the
+    /// discharge curve of a CR2477 battery: 3000 mV or more is 100%, 2900 mV 85%, 2800 mV 60%,
+    /// 2700 mV 35%, 2500 mV 15%, 2200 mV 5% and 2000 mV or less 0%. The voltage sags in the cold
+    /// and under load, so the estimate is rough.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}%", current_sensor_data.battery_percent());
+    /// }
+    /// ```
+    pub fn battery_percent(&self) -> u8 {
+        let battery_mv = self.get_battery_voltage();
+        let (full_mv, full_percent) = BATTERY_DISCHARGE_CURVE[0];
+        if battery_mv >= full_mv {
+            return full_percent;
+        }
+        BATTERY_DISCHARGE_CURVE
+            .windows(2)
+            .find(|points| battery_mv >= points[1].0)
+            .map(|points| {
+                let ((upper_mv, upper_percent), (lower_mv, lower_percent)) = (points[0], points[1]);
+                let percent = u32::from(lower_percent)
+                    + u32::from(upper_percent - lower_percent) * u32::from(battery_mv - lower_mv)
+                        / u32::from(upper_mv - lower_mv);
+                percent as u8
+            })
+            .unwrap_or(0)
+    }
+
+    /// Returns the current transmit power (dBm) measured from ruuvitag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.get_tx_power());
+    /// }
+    /// ```
+    pub fn get_tx_power(&self) -> i8 {
+        decode_tx_power(self.power_info)
+    }
+
+    /// Returns the number of movements detected by the ruuvitag, wraps around at 255.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.movement_counter());
+    /// }
+    /// ```
+    pub fn movement_counter(&self) -> u8 {
+        self.movement_counter
+    }
+
+    /// Returns the sequence number of the measurement, wraps around at 65535.
+    ///
+    /// Consecutive readings with the same measurement number are duplicates of the same
+    /// measurement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.measurement_number());
+    /// }
+    /// ```
+    pub fn measurement_number(&self) -> u16 {
+        self.measurement_number
+    }
+
+    /// Returns the data format of the data, always 5.
+    pub fn data_format(&self) -> u8 {
+        DATA_FORMAT_V5
+    }
+
+    /// Returns the name of the bluetooth adapter (e.g. `hci0`) that received the reading.
+    ///
+    /// `None` if the reading wasn't received through a subscription.
+    pub fn adapter(&self) -> Option<&str> {
+        self.adapter.as_deref()
+    }
+
+    /// Returns the received signal strength (dBm) of the advertisement that carried the reading.
+    ///
+    /// `None` if bluez didn't report the RSSI along with the manufacturer data.
+    pub fn rssi(&self) -> Option<i16> {
+        self.rssi
+    }
+
+    /// Checks that the mac address in the payload is `expected`.
+    ///
+    /// # Errors
+    ///
+    /// `RuuviError::MacMismatch` if the payload is from a different ruuvitag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let sensor_data = SensorDataV5::from_raw_bytes(&payload)?;
+    /// // Replace with your mac address.
+    /// sensor_data.verify_mac(&"CC:6F:70:EE:4C:AD".parse()?)?;
+    /// ```
+    pub fn verify_mac(&self, expected: &MacAddress) -> Result<(), RuuviError> {
+        if self.mac() != *expected {
+            return Err(RuuviError::MacMismatch {
+                expected: expected.to_string(),
+                got: self.mac_as_str(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the mac address of the measured ruuvitag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.mac_as_str());
+    /// }
+    /// ```
+    pub fn mac_as_str(&self) -> String {
+        let mut mac = String::with_capacity(17);
+        self.write_mac(&mut mac)
+            .expect("writing to a String doesn't fail");
+        mac
+    }
+
+    /// Writes the mac address of the measured ruuvitag to `f` without allocating.
+    ///
+    /// Prefer this over `mac_as_str` when formatting many readings, e.g. to a reused buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fmt::Write;
+    ///
+    /// let mut line = String::new();
+    /// while let Some(current_sensor_data) = rx.recv().await {
+    ///     line.clear();
+    ///     current_sensor_data.write_mac(&mut line)?;
+    ///     write!(line, " {}", current_sensor_data.temperature_in_celcius())?;
+    ///     println!("{line}");
+    /// }
+    /// ```
+    pub fn write_mac(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write_mac(&self.mac, f)
+    }
+
+    /// Returns the mac address of the measured ruuvitag as a `MacAddress`.
+    pub fn mac(&self) -> MacAddress {
+        MacAddress::from(self.mac)
+    }
+
+    /// Returns the partial pressure of water vapor in Pa.
+    ///
+    /// Calculated from the relative humidity and the saturation vapor pressure at the measured
+    /// temperature, which uses the Magnus-Tetens approximation
+    /// `p_s = 611.2 * exp(17.62 * t / (243.12 + t))`. The other humidity metrics are derived
+    /// from this value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.vapor_pressure_pa());
+    /// }
+    /// ```
+    pub fn vapor_pressure_pa(&self) -> f64 {
+        saturation_vapor_pressure_pa(self.temperature_in_celcius()) * self.get_humidity() / 100_f64
+    }
+
+    /// Returns the dew point in celsius.
+    ///
+    /// The temperature at which the current water vapor pressure would saturate, from the
+    /// inverted Magnus-Tetens approximation.
+    ///
+    /// `None` if the ruuvitag reports the temperature or the humidity as not available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.dew_point_celsius());
+    /// }
+    /// ```
+    pub fn dew_point_celsius(&self) -> Option<f64> {
+        if !self.temperature_is_valid() || !self.humidity_is_valid() {
+            return None;
+        }
+        let gamma = (self.vapor_pressure_pa() / MAGNUS_P0).ln();
+        Some(MAGNUS_B * gamma / (MAGNUS_A - gamma))
+    }
+
+    /// Returns the absolute humidity in grams of water vapor per cubic meter (g/m³).
+    ///
+    /// Calculated from the water vapor pressure with the ideal gas law. Unlike the relative
+    /// humidity it is comparable across temperatures, e.g. between indoor and outdoor air.
+    ///
+    /// `None` if the ruuvitag reports the temperature or the humidity as not available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.absolute_humidity());
+    /// }
+    /// ```
+    pub fn absolute_humidity(&self) -> Option<f64> {
+        if !self.temperature_is_valid() || !self.humidity_is_valid() {
+            return None;
+        }
+        Some(
+            self.vapor_pressure_pa() * 1000_f64
+                / (WATER_VAPOR_GAS_CONSTANT * self.temperature_in_kelvin()),
+        )
+    }
+
+    /// Returns the specific enthalpy of the moist air in kJ/kg of dry air.
+    ///
+    /// Calculated with the psychrometric formula `h = 1.006 * t + W * (2501 + 1.86 * t)`, where
+    /// `t` is the temperature in celsius and `W` is the humidity ratio
+    /// `W = 0.622 * p_w / (p - p_w)` of the water vapor partial pressure `p_w` and the
+    /// atmospheric pressure `p`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.enthalpy_kj_kg());
+    /// }
+    /// ```
+    pub fn enthalpy_kj_kg(&self) -> f64 {
+        let temperature = self.temperature_in_celcius();
+        let vapor_pressure = self.vapor_pressure_pa();
+        let humidity_ratio = 0.622 * vapor_pressure / (self.get_pressure() as f64 - vapor_pressure);
+        1.006 * temperature + humidity_ratio * (2501_f64 + 1.86 * temperature)
+    }
+
+    /// Returns all of the measured, computed and derived values in a single snapshot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{:?}", current_sensor_data.full_report());
+    /// }
+    /// ```
+    pub fn full_report(&self) -> FullReport {
+        FullReport {
+            raw_temperature: self.temperature,
+            raw_humidity: self.humidity,
+            raw_pressure: self.pressure,
+            raw_acceleration: [
+                self.acceleration.x,
+                self.acceleration.y,
+                self.acceleration.z,
+            ],
+            raw_power_info: self.power_info,
+            movement_counter: self.movement_counter,
+            measurement_number: self.measurement_number,
+            mac: self.mac_as_str(),
+            temperature_celsius: self.temperature_in_celcius(),
+            humidity_percent: self.get_humidity(),
+            pressure_pa: self.get_pressure(),
+            battery_voltage_mv: self.get_battery_voltage(),
+            tx_power_dbm: self.get_tx_power(),
+            dew_point_celsius: self.dew_point_celsius(),
+            absolute_humidity: self.absolute_humidity(),
+            acceleration_magnitude_mg: self.acceleration.magnitude_mg(),
+        }
+    }
+
+    /// Returns a compact one-line summary of the key values measured by the ruuvitag.
+    ///
+    /// Same as the `Display` form of the sensor data, e.g.
+    /// `CC:6F:70:EE:4C:AD 21.5°C 45% 1003hPa bat=2900mV`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     println!("{}", current_sensor_data.summary());
+    /// }
+    /// ```
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+
+    /// Prints diagnostic information about the measured ruuvitag.
+    ///
+    /// Prints all of the data measured by the ruuvitag in a
+    /// diagnostic format to ease development. Same as `println!("{sensor_data:#}")`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     current_sensor_data.print_sensor_data();
+    /// }
+    /// ```
+    pub fn print_sensor_data(&self) {
+        println!("{self:#}");
+    }
+
+    /// Writes diagnostic information about the measured ruuvitag to `writer`.
+    ///
+    /// Writes the same output as `print_sensor_data`, e.g. to a buffered stdout, a file or a
+    /// socket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// let mut stdout = std::io::stdout().lock();
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     current_sensor_data.write_sensor_data(&mut stdout)?;
+    /// }
+    /// ```
+    pub fn write_sensor_data<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "{self:#}")
+    }
+}
+
+impl SensorDataV5 {
+    /// Returns the fields decoded from the payload, leaving out the reception metadata.
+    fn payload_fields(&self) -> (i16, u16, u16, &Acceleration, u16, u8, u16, [u8; 6]) {
+        (
+            self.temperature,
+            self.humidity,
+            self.pressure,
+            &self.acceleration,
+            self.power_info,
+            self.movement_counter,
+            self.measurement_number,
+            self.mac,
+        )
+    }
+}
+
+impl PartialEq for SensorDataV5 {
+    fn eq(&self, other: &Self) -> bool {
+        self.payload_fields() == other.payload_fields()
+    }
+}
+
+impl Eq for SensorDataV5 {}
+
+impl Hash for SensorDataV5 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.payload_fields().hash(state);
+    }
+}
+
+impl fmt::Display for SensorDataV5 {
+    /// Formats the key values measured by the ruuvitag on one line, e.g.
+    /// `CC:6F:70:EE:4C:AD 21.5°C 45% 1003hPa bat=2900mV`.
+    ///
+    /// The alternate form `{:#}` formats all of the data measured by the ruuvitag in the
+    /// diagnostic format of `print_sensor_data`, one value per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !f.alternate() {
+            self.write_mac(f)?;
+            return write!(
+                f,
+                " {:.1}°C {:.0}% {:.0}hPa bat={}mV",
+                self.temperature_in_celcius(),
+                self.get_humidity(),
+                self.get_pressure_hpa(),
+                self.get_battery_voltage()
+            );
+        }
+        writeln!(f, "MAC address: {:?}", self.mac_as_str())?;
+        writeln!(
+            f,
+            "temperature in millicelcius (°mC): {:?}",
+            self.temperature_in_millicelcius()
+        )?;
+        writeln!(
+            f,
+            "temperature in celcius (°C): {:?}",
+            self.temperature_in_celcius()
+        )?;
+        writeln!(f, "humidity (%): {:?}", self.get_humidity())?;
+        writeln!(f, "Atmospheric pressure (Pa): {:?}", self.get_pressure())?;
+        writeln!(f, "Acceleration (mG): {:?}", self.get_acceleration_in_mg())?;
+        writeln!(f, "Battery voltage (mV): {:?}", self.get_battery_voltage())?;
+        writeln!(f, "Tx Power (dBm): {:?}", self.get_tx_power())?;
+        writeln!(f, "Movement counter: {:?}", self.movement_counter)?;
+        writeln!(
+            f,
+            "Measurement sequence number: {:?}",
+            self.measurement_number
+        )?;
+        writeln!(f, "RSSI (dBm): {:?}", self.rssi)?;
+        Ok(())
+    }
+}
+
+/// A structure to hold ruuvitag data from V2 and V4 formats.
+///
+/// Ruuvitags in URL mode broadcast the data as an Eddystone-URL, e.g. `https://ruu.vi/#AjwYAMFc`,
+/// where the fragment is the base64 encoded payload. Data format 4 appends a tag identifier
+/// character which is ignored.
+/// <https://github.com/ruuvi/ruuvi-sensor-protocols/blob/master/dataformat_04.md>
+#[derive(Debug, Clone)]
+pub struct SensorDataV2 {
+    data_format: u8,
+    humidity: u8,
+    temperature_integer: u8,
+    temperature_fraction: u8,
+    pressure: u16,
+}
+
+impl SensorDataV2 {
+    /// Constructs a `SensorDataV2` from dbus message `PropertiesChanged`.
+    ///
+    /// Decodes the Eddystone-URL frame of the `ServiceData` property.
+    pub fn from_dbus_changed_properties(
+        changed_properties: arg::PropMap,
+    ) -> Result<Self, RuuviError> {
+        let service_data = match changed_properties.get("ServiceData") {
+            Some(x) => x,
+            None => return Err(RuuviError::MissingServiceData),
+        };
+        let mut entries = match service_data.0.as_iter() {
+            Some(x) => x,
+            None => {
+                return Err(RuuviError::Malformed(
+                    "ServiceData couldn't be collected".to_string(),
+                ))
+            }
+        };
+        // The dict is iterated as alternating keys and values.
+        while let Some(uuid) = entries.next() {
+            let value = match entries.next() {
+                Some(value) => value,
+                None => {
+                    return Err(RuuviError::Malformed(format!(
+                        "Missing value for service {uuid:?}"
+                    )))
+                }
+            };
+            if uuid.as_str() != Some(EDDYSTONE_SERVICE_UUID) {
+                continue;
+            }
+            // The value is a `Variant` of one list so make it a iterable and take the first
+            // element.
+            let frame = value
+                .as_iter()
+                .and_then(|mut x| x.next())
+                .and_then(|x| x.as_iter())
+                .ok_or_else(|| {
+                    RuuviError::Malformed(
+                        "Eddystone service data couldn't be collected".to_string(),
+                    )
+                })?
+                .map(|item| item.as_i64().map(|x| x as u8))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(|| {
+                    RuuviError::Malformed("Eddystone service data isn't a byte array".to_string())
+                })?;
+            return SensorDataV2::from_eddystone_url_frame(&frame);
+        }
+        Err(RuuviError::MissingServiceData)
+    }
+
+    /// Constructs a `SensorDataV2` from the service data of an Eddystone-URL frame.
+    pub fn from_eddystone_url_frame(frame: &[u8]) -> Result<Self, RuuviError> {
+        if frame.first() != Some(&EDDYSTONE_URL_FRAME) {
+            return Err(RuuviError::Malformed(format!(
+                "Not an Eddystone-URL frame {frame:?}"
+            )));
+        }
+        // Frame type, tx power and URL scheme precede the encoded URL.
+        let url = frame
+            .get(3..)
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .ok_or_else(|| {
+                RuuviError::Malformed(format!("Invalid Eddystone-URL frame {frame:?}"))
+            })?;
+        SensorDataV2::from_url(url)
+    }
+
+    /// Constructs a `SensorDataV2` from the URL broadcast by the ruuvitag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ruuviscanner::ruuvitag::SensorDataV2;
+    ///
+    /// let sensor_data = SensorDataV2::from_url("https://ruu.vi/#AjwYAMFc").unwrap();
+    /// assert_eq!(sensor_data.temperature_in_celcius(), 24.0);
+    /// ```
+    pub fn from_url(url: &str) -> Result<Self, RuuviError> {
+        let encoded = match url.split_once('#') {
+            Some((_, encoded)) => encoded,
+            None => {
+                return Err(RuuviError::Malformed(format!(
+                    "Missing ruuvi data in URL {url:?}"
+                )))
+            }
+        };
+        let temp = decode_base64(encoded)?;
+        if temp.len() < 6 {
+            return Err(RuuviError::WrongLength {
+                expected: 6,
+                got: temp.len(),
+            });
+        }
+        if temp[0] != DATA_FORMAT_V2 && temp[0] != DATA_FORMAT_V4 {
+            return Err(RuuviError::UnsupportedFormat(temp[0]));
+        }
+        Ok(SensorDataV2 {
+            data_format: temp[0],
+            humidity: temp[1],
+            temperature_integer: temp[2],
+            temperature_fraction: temp[3],
+            pressure: join_u8(temp[4], temp[5]),
+        })
+    }
+
+    /// Returns the data format, 2 or 4.
+    pub fn data_format(&self) -> u8 {
+        self.data_format
+    }
+
+    /// Returns temperature in millicelcius.
+    pub fn temperature_in_millicelcius(&self) -> i32 {
+        sign_magnitude_millicelcius(self.temperature_integer, self.temperature_fraction)
+    }
+
+    /// Returns temperature in celcius.
+    pub fn temperature_in_celcius(&self) -> f64 {
+        self.temperature_in_millicelcius() as f64 / 1000_f64
+    }
+
+    /// Returns humidity in %, in 0.5% steps.
+    pub fn get_humidity(&self) -> f64 {
+        self.humidity as f64 / 2_f64
+    }
+
+    /// Returns pressure in Pa.
+    pub fn get_pressure(&self) -> u32 {
+        self.pressure as u32 + PRESSURE_OFFSET
+    }
+}
+
+/// A structure to hold ruuvitag data from V3 format.
+///
+/// Implementation following ruuvi data format 3 (RAWv1). The format doesn't carry the mac
+/// address, the movement counter or the measurement number of the tag.
+/// <https://github.com/ruuvi/ruuvi-sensor-protocols/blob/master/dataformat_03.md>
+#[derive(Debug, Clone)]
+pub struct SensorDataV3 {
+    humidity: u8,
+    temperature_integer: u8,
+    temperature_fraction: u8,
+    pressure: u16,
+    acceleration: Acceleration,
+    battery_voltage: u16,
+}
+
+impl SensorDataV3 {
+    /// Constructs a `SensorDataV3` from dbus message `PropertiesChanged`.
+    pub fn from_dbus_changed_properties(
+        changed_properties: arg::PropMap,
+    ) -> Result<Self, RuuviError> {
+        let temp = ruuvi_manufacturer_data(&changed_properties)?;
+        SensorDataV3::from_raw_bytes(&temp)
+    }
+
+    /// Constructs a `SensorDataV3` from the 14 byte manufacturer data payload.
+    pub fn from_raw_bytes(temp: &[u8]) -> Result<Self, RuuviError> {
+        if temp.len() != 14 {
+            return Err(RuuviError::WrongLength {
+                expected: 14,
+                got: temp.len(),
+            });
+        }
+        if temp[0] != DATA_FORMAT_V3 {
+            return Err(RuuviError::UnsupportedFormat(temp[0]));
+        }
+        Ok(SensorDataV3 {
+            humidity: temp[1],
+            temperature_integer: temp[2],
+            temperature_fraction: temp[3],
+            pressure: join_u8(temp[4], temp[5]),
+            acceleration: Acceleration {
+                x: join_u8(temp[6], temp[7]) as i16,
+                y: join_u8(temp[8], temp[9]) as i16,
+                z: join_u8(temp[10], temp[11]) as i16,
+            },
+            battery_voltage: join_u8(temp[12], temp[13]),
+        })
+    }
+
+    /// Returns the data format of the data, always 3.
+    pub fn data_format(&self) -> u8 {
+        DATA_FORMAT_V3
+    }
+
+    /// Returns temperature in millicelcius.
+    ///
+    /// The temperature is encoded as a sign bit, the integer part and the hundredths.
+    pub fn temperature_in_millicelcius(&self) -> i32 {
+        sign_magnitude_millicelcius(self.temperature_integer, self.temperature_fraction)
+    }
+
+    /// Returns temperature in celcius.
+    pub fn temperature_in_celcius(&self) -> f64 {
+        self.temperature_in_millicelcius() as f64 / 1000_f64
+    }
+
+    /// Returns humidity in %, in 0.5% steps.
+    pub fn get_humidity(&self) -> f64 {
+        self.humidity as f64 / 2_f64
+    }
+
+    /// Returns pressure in Pa.
+    pub fn get_pressure(&self) -> u32 {
+        self.pressure as u32 + PRESSURE_OFFSET
+    }
+
+    /// Returns acceleration in mG.
+    pub fn get_acceleration_in_mg(&self) -> &Acceleration {
+        &self.acceleration
+    }
+
+    /// Returns battery voltage in mV.
+    pub fn get_battery_voltage(&self) -> u16 {
+        self.battery_voltage
+    }
+}
+
+/// Units of the temperature returned by [`SensorDataV5::temperature_in`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+    Millicelsius,
+}
+
+impl TemperatureUnit {
+    /// Returns the symbol of the unit, e.g. `°C`.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+            TemperatureUnit::Kelvin => "K",
+            TemperatureUnit::Millicelsius => "m°C",
+        }
+    }
+
+    /// Converts a temperature in millicelsius to the unit.
+    fn convert_millicelcius(&self, millicelcius: i32) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => f64::from(millicelcius) / 1000_f64,
+            TemperatureUnit::Fahrenheit => f64::from(millicelcius) * 9_f64 / 5000_f64 + 32_f64,
+            TemperatureUnit::Kelvin => f64::from(millicelcius + 273_150) / 1000_f64,
+            TemperatureUnit::Millicelsius => f64::from(millicelcius),
+        }
+    }
+}
+
+/// Measured fields of `SensorDataV5` that the ruuvitag can report as invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SensorField {
+    Temperature,
+    Humidity,
+    Pressure,
+    Acceleration,
+}
+
+/// Structure to hold acceleration information (X, Y, Z)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Acceleration {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+}
+
+impl Acceleration {
+    /// Constructs a `Acceleration` structure.
+    pub fn new(x: i16, y: i16, z: i16) -> Acceleration {
+        Acceleration { x, y, z }
+    }
+
+    /// Returns the acceleration along the X axis in mG, `None` if not available (raw value
+    /// `0x8000`).
+    pub fn try_x(&self) -> Option<i16> {
+        valid_axis(self.x)
+    }
+
+    /// Returns the acceleration along the Y axis in mG, `None` if not available (raw value
+    /// `0x8000`).
+    pub fn try_y(&self) -> Option<i16> {
+        valid_axis(self.y)
+    }
+
+    /// Returns the acceleration along the Z axis in mG, `None` if not available (raw value
+    /// `0x8000`).
+    pub fn try_z(&self) -> Option<i16> {
+        valid_axis(self.z)
+    }
+
+    /// Returns the magnitude of the acceleration vector in mG.
+    pub fn magnitude_mg(&self) -> f64 {
+        let (x, y, z) = (self.x as f64, self.y as f64, self.z as f64);
+        (x * x + y * y + z * z).sqrt()
+    }
+
+    /// Returns the acceleration along the X, Y and Z axes in G.
+    pub fn in_g(&self) -> (f64, f64, f64) {
+        (
+            self.x as f64 / 1000_f64,
+            self.y as f64 / 1000_f64,
+            self.z as f64 / 1000_f64,
+        )
+    }
+
+    /// Returns `true` if the magnitude of the acceleration is below `threshold_mg`.
+    ///
+    /// At rest the tag measures a magnitude of about 1000 mG due to gravity, a falling tag
+    /// measures close to 0 mG. See [`DEFAULT_FREE_FALL_THRESHOLD_MG`]. A single reading only
+    /// catches a fall that is in progress at the moment the tag samples the acceleration.
+    pub fn is_free_fall(&self, threshold_mg: f64) -> bool {
+        self.is_valid() && self.magnitude_mg() < threshold_mg
+    }
+
+    /// Returns `true` if the magnitude of the acceleration is above `threshold_mg`.
+    ///
+    /// At rest the tag measures a magnitude of about 1000 mG due to gravity, a dropped or
+    /// knocked tag spikes well above it. See [`DEFAULT_IMPACT_THRESHOLD_MG`].
+    pub fn is_impact(&self, threshold_mg: f64) -> bool {
+        self.is_valid() && self.magnitude_mg() > threshold_mg
+    }
+
+    /// Returns `false` if any axis is reported as not available.
+    fn is_valid(&self) -> bool {
+        self.try_x().is_some() && self.try_y().is_some() && self.try_z().is_some()
+    }
+
+    /// Returns which side of the tag faces up, assuming the tag is at rest.
+    ///
+    /// At rest the accelerometer measures 1 G along the axis pointing up. Uses
+    /// [`ORIENTATION_TOLERANCE_MG`] as the tolerance, see `orientation_with_tolerance`.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation_with_tolerance(ORIENTATION_TOLERANCE_MG)
+    }
+
+    /// Returns which side of the tag faces up, assuming the tag is at rest.
+    ///
+    /// The dominant axis decides the orientation if its acceleration is within `tolerance_mg`
+    /// of ±1000 mG, otherwise the orientation is `Orientation::Unknown`, e.g. when the tag is
+    /// tilted or moving.
+    pub fn orientation_with_tolerance(&self, tolerance_mg: i16) -> Orientation {
+        let (Some(x), Some(y), Some(z)) = (self.try_x(), self.try_y(), self.try_z()) else {
+            return Orientation::Unknown;
+        };
+        let (axis, value) = [(Axis::X, x), (Axis::Y, y), (Axis::Z, z)]
+            .into_iter()
+            .max_by_key(|(_, value)| value.unsigned_abs())
+            .unwrap();
+        if (i32::from(value.unsigned_abs()) - 1000).abs() > i32::from(tolerance_mg) {
+            return Orientation::Unknown;
+        }
+        match (axis, value > 0) {
+            (Axis::Z, true) => Orientation::FaceUp,
+            (Axis::Z, false) => Orientation::FaceDown,
+            (axis, positive) => Orientation::OnSide { axis, positive },
+        }
+    }
+}
+
+/// Axes of the accelerometer of the ruuvitag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// Orientation of the ruuvitag, returned by [`Acceleration::orientation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    /// The Z axis points up.
+    FaceUp,
+    /// The Z axis points down.
+    FaceDown,
+    /// The X or Y `axis` points up if `positive`, otherwise down.
+    OnSide { axis: Axis, positive: bool },
+    /// No axis points up or down, or the acceleration is not available.
+    Unknown,
+}
+
+impl From<Acceleration> for [i16; 3] {
+    fn from(acceleration: Acceleration) -> Self {
+        [acceleration.x, acceleration.y, acceleration.z]
+    }
+}
+
+impl From<[i16; 3]> for Acceleration {
+    fn from([x, y, z]: [i16; 3]) -> Self {
+        Acceleration::new(x, y, z)
+    }
+}
+
+impl From<Acceleration> for (i16, i16, i16) {
+    fn from(acceleration: Acceleration) -> Self {
+        (acceleration.x, acceleration.y, acceleration.z)
+    }
+}
+
+impl From<(i16, i16, i16)> for Acceleration {
+    fn from((x, y, z): (i16, i16, i16)) -> Self {
+        Acceleration::new(x, y, z)
+    }
+}
+
+/// Combines an axis of two accelerations with `op`.
+///
+/// The result is not available if either axis is, and saturates to `-i16::MAX..=i16::MAX` so
+/// that an overflowing result isn't mistaken for not available.
+fn combine_axis(a: i16, b: i16, op: fn(i16, i16) -> i16) -> i16 {
+    if a == INVALID_ACCELERATION || b == INVALID_ACCELERATION {
+        return INVALID_ACCELERATION;
+    }
+    op(a, b).max(-i16::MAX)
+}
+
+impl Add for Acceleration {
+    type Output = Acceleration;
+
+    /// Adds the accelerations axis by axis.
+    ///
+    /// An axis that is not available in either acceleration is not available in the sum. The
+    /// sum saturates to `-i16::MAX..=i16::MAX`.
+    fn add(self, other: Acceleration) -> Acceleration {
+        Acceleration::new(
+            combine_axis(self.x, other.x, i16::saturating_add),
+            combine_axis(self.y, other.y, i16::saturating_add),
+            combine_axis(self.z, other.z, i16::saturating_add),
+        )
+    }
+}
+
+impl Sub for Acceleration {
+    type Output = Acceleration;
+
+    /// Subtracts the accelerations axis by axis, e.g. to compute the change between two
+    /// successive readings.
+    ///
+    /// An axis that is not available in either acceleration is not available in the difference.
+    /// The difference saturates to `-i16::MAX..=i16::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Replace with your mac address.
+    /// let mac = "CC:6F:70:EE:4C:AD";
+    /// let mut rx = subscribe_ruuvitag(mac).await?;
+    /// let mut previous: Option<Acceleration> = None;
+    /// loop {
+    ///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+    ///     let acceleration = *current_sensor_data.get_acceleration_in_mg();
+    ///     if let Some(previous) = previous {
+    ///         println!("{:?}", acceleration - previous);
+    ///     }
+    ///     previous = Some(acceleration);
+    /// }
+    /// ```
+    fn sub(self, other: Acceleration) -> Acceleration {
+        Acceleration::new(
+            combine_axis(self.x, other.x, i16::saturating_sub),
+            combine_axis(self.y, other.y, i16::saturating_sub),
+            combine_axis(self.z, other.z, i16::saturating_sub),
+        )
+    }
+}
+
+impl Index<usize> for Acceleration {
+    type Output = i16;
+
+    /// Returns the acceleration along the X axis for `0`, Y for `1` and Z for `2`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is greater than 2.
+    fn index(&self, index: usize) -> &i16 {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("acceleration has 3 axes but the index is {index}"),
+        }
+    }
+}
+
+/// A snapshot of the raw, computed and derived values of a `SensorDataV5`.
+///
+/// Returned by [`SensorDataV5::full_report`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FullReport {
+    pub raw_temperature: i16,
+    pub raw_humidity: u16,
+    pub raw_pressure: u16,
+    pub raw_acceleration: [i16; 3],
+    pub raw_power_info: u16,
+    pub movement_counter: u8,
+    pub measurement_number: u16,
+    pub mac: String,
+    pub temperature_celsius: f64,
+    pub humidity_percent: f64,
+    pub pressure_pa: u32,
+    pub battery_voltage_mv: u16,
+    pub tx_power_dbm: i8,
+    /// `None` if the temperature or the humidity is not available.
+    pub dew_point_celsius: Option<f64>,
+    /// Absolute humidity in g/m³, `None` if the temperature or the humidity is not available.
+    pub absolute_humidity: Option<f64>,
+    pub acceleration_magnitude_mg: f64,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::bluetooth::ConnectOptions;
+    use crate::error::RuuviError;
+    use crate::mac::MacAddress;
+    use crate::ruuvitag::{
+        added_ruuvitag, decode, decode_battery_voltage, decode_tx_power, forward,
+        forward_broadcast, has_ruuvi_manufacturer_data, report, subscribe_ruuvitag,
+        subscribe_ruuvitags, Acceleration, Axis, LatestReading, Orientation, ReadingIterator,
+        ReconnectOptions, RoundRobin, RuuviData, SensorDataStream, SensorDataV2, SensorDataV3,
+        SensorDataV5, SubscribeOptions, SubscriptionCounters, SubscriptionHandle, TagDecoder,
+        TemperatureUnit, BATTERY_OFFSET, DEFAULT_CHANNEL_CAPACITY, DEFAULT_FREE_FALL_THRESHOLD_MG,
+        DEFAULT_IMPACT_THRESHOLD_MG, INVALID_ACCELERATION, INVALID_HUMIDITY, INVALID_PRESSURE,
+        INVALID_TEMPERATURE, LOW_BATTERY_THRESHOLD_MV, STANDARD_SEA_LEVEL_PRESSURE_PA,
+        TAG_CHANNEL_CAPACITY, TX_POWER_OFFSET,
+    };
+    use crate::testing::PayloadBuilder;
+    use dbus::arg::{PropMap, RefArg, Variant};
+    use dbus::nonblock::stdintf::org_freedesktop_dbus::ObjectManagerInterfacesAdded;
+    use dbus::Path;
+    use futures_util::StreamExt;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::broadcast::{
+        self,
+        error::{RecvError, TryRecvError},
+    };
+    use tokio::sync::mpsc::channel;
+
+    #[test]
+    fn test_ruuvitag_sensor_data_v5_min() {
+        let sensor_data = SensorDataV5::new(
+            i16::MIN,
+            u16::MIN,
+            u16::MIN,
+            Acceleration::new(i16::MIN, i16::MIN, i16::MIN),
+            u16::MIN,
+            u8::MIN,
+            u16::MIN,
+            [u8::MIN, u8::MIN, u8::MIN, u8::MIN, u8::MIN, u8::MIN],
+        );
+        assert_eq!(sensor_data.try_temperature_in_millicelcius(), None);
+        assert_eq!(sensor_data.try_temperature_in_celcius(), None);
+        assert_eq!(sensor_data.try_temperature_in_fahrenheit(), None);
+        assert_eq!(sensor_data.try_temperature_in_kelvin(), None);
+    }
+
+    #[tokio::test]
+    async fn test_ruuvitag_forward_stops_when_receiver_is_dropped() {
+        let counters = Arc::new(SubscriptionCounters::default());
+        let handle = SubscriptionHandle {
+            label: Arc::from("test"),
+            counters: Arc::clone(&counters),
+        };
+        let reading = || {
+            SensorDataV5::new(
+                0,
+                0,
+                0,
+                Acceleration::new(0, 0, 0),
+                0,
+                0,
+                0,
+                [0, 0, 0, 0, 0, 0],
+            )
+        };
+        let (tx, rx) = channel(1);
+        assert!(forward(&tx, &counters, reading()).await);
+        assert!(!handle.is_closed());
+
+        drop(rx);
+        assert!(!forward(&tx, &counters, reading()).await);
+        assert!(handle.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_rejects_invalid_mac() {
+        let result = subscribe_ruuvitag("not-a-mac").await;
+        assert!(matches!(result, Err(RuuviError::InvalidMac(mac)) if mac == "not-a-mac"));
+        let result = subscribe_ruuvitags(&["CC:6F:70:EE:4C:AD", "CC_6F_70_EE_4C_AD"]).await;
+        assert!(matches!(result, Err(RuuviError::InvalidMac(mac)) if mac == "CC_6F_70_EE_4C_AD"));
+    }
+
+    #[test]
+    fn test_subscription_report_discards_when_full() {
+        report(&None, RuuviError::ConnectionLost);
+        let (tx, mut rx) = channel(1);
+        let errors = Some(tx);
+        report(&errors, RuuviError::UnsupportedFormat(3));
+        report(&errors, RuuviError::ConnectionLost);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(RuuviError::UnsupportedFormat(3))
+        ));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_subscribe_options_builder() {
+        let options = SubscribeOptions::new("CC:6F:70:EE:4C:AD");
+        assert_eq!(options.label, None);
+        assert_eq!(options.connect, ConnectOptions::default());
+        assert_eq!(options.capacity, DEFAULT_CHANNEL_CAPACITY);
+        assert!(!options.verify_mac);
+        assert!(options.errors.is_none());
+        assert_eq!(options.reconnect, None);
+
+        let options = options
+            .label("sauna")
+            .adapter("hci1")
+            .timeout(Duration::from_secs(1))
+            .discovery_filter(None)
+            .capacity(64)
+            .verify_mac(true)
+            .errors(channel(1).0)
+            .reconnect(ReconnectOptions::default());
+        assert_eq!(options.mac_address, "CC:6F:70:EE:4C:AD");
+        assert_eq!(options.label.as_deref(), Some("sauna"));
+        assert_eq!(options.connect.adapter, "hci1");
+        assert_eq!(options.connect.timeout, Duration::from_secs(1));
+        assert_eq!(options.connect.discovery_filter, None);
+        assert_eq!(options.capacity, 64);
+        assert!(options.verify_mac);
+        assert!(options.errors.is_some());
+        assert_eq!(options.reconnect, Some(ReconnectOptions::default()));
+    }
+
+    #[test]
+    fn test_reconnect_options_backoff() {
+        let reconnect = ReconnectOptions {
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            ..ReconnectOptions::default()
+        };
+        assert_eq!(reconnect.backoff(0), Duration::from_millis(500));
+        assert_eq!(reconnect.backoff(1), Duration::from_secs(1));
+        assert_eq!(reconnect.backoff(4), Duration::from_secs(8));
+        assert_eq!(reconnect.backoff(5), Duration::from_secs(10));
+        assert_eq!(reconnect.backoff(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_ruuvitag_sensor_data_stream_ends_with_subscription() {
+        let (tx, rx) = channel(1);
+        let mut stream = SensorDataStream { rx };
+        let reading = SensorDataV5::new(
+            0,
+            0,
+            0,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            205,
+            [0, 0, 0, 0, 0, 0],
+        );
+        tx.send(reading).await.unwrap();
+        drop(tx);
+        assert_eq!(stream.next().await.unwrap().measurement_number(), 205);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[test]
+    fn test_ruuvitag_reading_iterator_ends_with_subscription() {
+        let (tx, rx) = channel(2);
+        let reading = |measurement_number| {
+            SensorDataV5::new(
+                0,
+                0,
+                0,
+                Acceleration::new(0, 0, 0),
+                0,
+                0,
+                measurement_number,
+                [0, 0, 0, 0, 0, 0],
+            )
+        };
+        tx.try_send(reading(205)).unwrap();
+        tx.try_send(reading(206)).unwrap();
+        drop(tx);
+        let measurement_numbers: Vec<u16> = ReadingIterator::new(rx)
+            .map(|reading| reading.measurement_number())
+            .collect();
+        assert_eq!(measurement_numbers, [205, 206]);
+    }
+
+    #[tokio::test]
+    async fn test_forward_broadcast() {
+        let (tx, rx) = channel(DEFAULT_CHANNEL_CAPACITY);
+        let (broadcast_tx, mut first) = broadcast::channel(2);
+        let mut second = first.resubscribe();
+        let forwarding = tokio::spawn(forward_broadcast(rx, broadcast_tx));
+        for measurement_number in 205..=207 {
+            tx.send(measurement_number).await.unwrap();
+        }
+        drop(tx);
+        forwarding.await.unwrap();
+
+        assert_eq!(first.recv().await, Err(RecvError::Lagged(1)));
+        assert_eq!(first.recv().await, Ok(206));
+        assert_eq!(first.recv().await, Ok(207));
+        assert_eq!(second.try_recv(), Err(TryRecvError::Lagged(1)));
+        assert_eq!(second.try_recv(), Ok(206));
+        assert_eq!(first.recv().await, Err(RecvError::Closed));
+
+        let (tx, rx) = channel(DEFAULT_CHANNEL_CAPACITY);
+        let (broadcast_tx, broadcast_rx) = broadcast::channel(2);
+        let forwarding = tokio::spawn(forward_broadcast(rx, broadcast_tx));
+        drop(broadcast_rx);
+        tx.send(205).await.unwrap();
+        forwarding.await.unwrap();
+        assert!(tx.is_closed());
+    }
+
+    #[test]
+    fn test_latest_reading() {
+        let (tx, rx) = channel(DEFAULT_CHANNEL_CAPACITY);
+        let mut latest = LatestReading::from(rx);
+        assert_eq!(latest.try_latest(), None);
+        for measurement_number in 205..=207 {
+            tx.try_send(measurement_number).unwrap();
+        }
+        assert_eq!(latest.try_latest(), Some(207));
+        assert_eq!(latest.try_latest(), None);
+        assert!(!latest.is_closed());
+
+        tx.try_send(208).unwrap();
+        drop(tx);
+        assert_eq!(latest.try_latest(), Some(208));
+        assert!(latest.is_closed());
+        assert_eq!(latest.try_latest(), None);
+    }
+
+    #[test]
+    fn test_ruuvitag_validity_predicates() {
+        let sensor_data = SensorDataV5::new(
+            i16::MIN,
+            u16::MAX,
+            u16::MAX,
+            Acceleration::new(0, i16::MIN, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert!(!sensor_data.temperature_is_valid());
+        assert!(!sensor_data.humidity_is_valid());
+        assert!(!sensor_data.pressure_is_valid());
+        assert!(!sensor_data.acceleration_is_valid());
+
+        let sensor_data = SensorDataV5::new(
+            0,
+            0,
+            0,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert!(sensor_data.temperature_is_valid());
+        assert!(sensor_data.humidity_is_valid());
+        assert!(sensor_data.pressure_is_valid());
+        assert!(sensor_data.acceleration_is_valid());
+    }
+
+    #[test]
+    fn test_ruuvitag_try_accessors() {
+        let sensor_data = SensorDataV5::new(
+            0,
+            u16::MAX,
+            u16::MAX,
+            Acceleration::new(4, i16::MIN, 1036),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert_eq!(sensor_data.try_get_humidity(), None);
+        assert_eq!(sensor_data.try_get_pressure(), None);
+        let acceleration = sensor_data.get_acceleration_in_mg();
+        assert_eq!(acceleration.try_x(), Some(4));
+        assert_eq!(acceleration.try_y(), None);
+        assert_eq!(acceleration.try_z(), Some(1036));
+
+        let sensor_data = SensorDataV5::new(
+            0,
+            20000,
+            51325,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert_eq!(sensor_data.try_get_humidity(), Some(50.0));
+        assert_eq!(sensor_data.try_get_pressure(), Some(101325));
+    }
+
+    #[test]
+    fn test_ruuvitag_pressure_conversions() {
+        // 51325 + 50000 = 101325 Pa, the standard atmosphere.
+        let sensor_data = SensorDataV5::new(
+            0,
+            0,
+            51325,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert!((sensor_data.try_get_pressure_hpa().unwrap() - 1013.25).abs() < 1e-9);
+        assert!((sensor_data.try_get_pressure_mmhg().unwrap() - 760.0).abs() < 0.01);
+        assert!((sensor_data.try_get_pressure_inhg().unwrap() - 29.92).abs() < 0.01);
+        assert!(
+            sensor_data
+                .altitude_meters(STANDARD_SEA_LEVEL_PRESSURE_PA)
+                .unwrap()
+                .abs()
+                < 1e-9
+        );
+        // ~12 Pa per meter near sea level.
+        assert!((sensor_data.altitude_meters(102525).unwrap() - 99.0).abs() < 2.0);
+
+        let sensor_data = SensorDataV5::new(
+            0,
+            0,
+            INVALID_PRESSURE,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert_eq!(sensor_data.try_get_pressure_hpa(), None);
+        assert_eq!(sensor_data.try_get_pressure_mmhg(), None);
+        assert_eq!(sensor_data.try_get_pressure_inhg(), None);
+        assert_eq!(
+            sensor_data.altitude_meters(STANDARD_SEA_LEVEL_PRESSURE_PA),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ruuvitag_battery_percent() {
+        let battery_percent = |battery_mv: u16| {
+            SensorDataV5::new(
+                0,
+                0,
+                0,
+                Acceleration::new(0, 0, 0),
+                (battery_mv - 1600) << 5,
+                0,
+                0,
+                [0, 0, 0, 0, 0, 0],
+            )
+            .battery_percent()
+        };
+        assert_eq!(battery_percent(3100), 100);
+        assert_eq!(battery_percent(3000), 100);
+        assert_eq!(battery_percent(2850), 72);
+        assert_eq!(battery_percent(2600), 25);
+        assert_eq!(battery_percent(2000), 0);
+        assert_eq!(battery_percent(1700), 0);
+    }
+
+    #[test]
+    fn test_ruuvitag_battery_is_low() {
+        let sensor_data = SensorDataV5::new(
+            0,
+            0,
+            0,
+            Acceleration::new(0, 0, 0),
+            (2499 - 1600) << 5,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert!(sensor_data.battery_is_low(LOW_BATTERY_THRESHOLD_MV));
+        assert!(!sensor_data.battery_is_low(2400));
+    }
+
+    #[test]
+    fn test_ruuvitag_operative_temperature() {
+        // 4000 * 0.005 = 20 °C air temperature.
+        let sensor_data = SensorDataV5::new(
+            4000,
+            0,
+            0,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert!((sensor_data.operative_temperature(24.0, 0.1) - 22.0).abs() < 1e-9);
+        assert!((sensor_data.operative_temperature(30.0, 0.8) - 23.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ruuvitag_full_report() {
+        let sensor_data = SensorDataV5::new(
+            4860,
+            21396,
+            50044,
+            Acceleration::new(0, 0, 1000),
+            0,
+            66,
+            205,
+            [0xCC, 0x6F, 0x70, 0xEE, 0x4C, 0xAD],
+        );
+        let report = sensor_data.full_report();
+        assert_eq!(report.raw_temperature, 4860);
+        assert_eq!(report.mac, "CC:6F:70:EE:4C:AD");
+        assert_eq!(report.pressure_pa, 100044);
+        assert_eq!(report.acceleration_magnitude_mg, 1000.0);
+        assert!((report.dew_point_celsius.unwrap() - 14.3).abs() < 0.1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ruuvitag_full_report_serialize() {
+        let sensor_data = SensorDataV5::new(
+            4860,
+            21396,
+            50044,
+            Acceleration::new(0, 0, 1000),
+            0,
+            66,
+            205,
+            [0xCC, 0x6F, 0x70, 0xEE, 0x4C, 0xAD],
+        );
+        let json = serde_json::to_string(&sensor_data.full_report()).unwrap();
+        assert!(json.starts_with("{\"raw_temperature\":4860,\"raw_humidity\":21396,"));
+        assert!(json.contains("\"raw_acceleration\":[0,0,1000],"));
+        assert!(json.contains("\"mac\":\"CC:6F:70:EE:4C:AD\","));
+        assert!(json.contains("\"pressure_pa\":100044,"));
+        assert!(json.ends_with("\"acceleration_magnitude_mg\":1000.0}"));
+    }
+
+    #[test]
+    fn test_ruuvitag_from_advertisement_bytes() {
+        let advertisement = [
+            0x02, 0x01, 0x06, 0x1B, 0xFF, 0x99, 0x04, 0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C,
+            0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC, 0x36, 0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33,
+            0x4C, 0x88, 0x4F,
+        ];
+        let sensor_data = SensorDataV5::from_advertisement_bytes(&advertisement).unwrap();
+        assert_eq!(sensor_data.temperature_in_millicelcius(), 24300);
+        assert_eq!(sensor_data.try_temperature_in_millicelcius(), Some(24300));
+        assert_eq!(sensor_data.get_pressure(), 100044);
+        assert_eq!(sensor_data.measurement_number(), 205);
+        assert_eq!(sensor_data.mac_as_str(), "CB:B8:33:4C:88:4F");
+    }
+
+    #[test]
+    fn test_ruuvitag_from_advertisement_bytes_malformed() {
+        // Length of the second AD structure exceeds the advertisement.
+        assert!(SensorDataV5::from_advertisement_bytes(&[0x02, 0x01, 0x06, 0x1B, 0xFF]).is_err());
+        // Manufacturer data of another company.
+        assert!(SensorDataV5::from_advertisement_bytes(&[0x03, 0xFF, 0x4C, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_ruuvitag_from_dbus_changed_properties_with_other_manufacturer() {
+        let payload: Vec<u8> = vec![
+            0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC,
+            0x36, 0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F,
+        ];
+        let mut manufacturer_data: HashMap<u16, Variant<Box<dyn RefArg>>> = HashMap::new();
+        manufacturer_data.insert(0x004C, Variant(Box::new(vec![0x02_u8, 0x15])));
+        manufacturer_data.insert(0x0499, Variant(Box::new(payload)));
+        let mut changed_properties: PropMap = HashMap::new();
+        changed_properties.insert(
+            "ManufacturerData".to_string(),
+            Variant(Box::new(manufacturer_data)),
+        );
+
+        changed_properties.insert("RSSI".to_string(), Variant(Box::new(-70_i16)));
+        assert!(has_ruuvi_manufacturer_data(&changed_properties));
+
+        let sensor_data = SensorDataV5::from_dbus_changed_properties(changed_properties).unwrap();
+        assert_eq!(sensor_data.mac_as_str(), "CB:B8:33:4C:88:4F");
+        assert_eq!(sensor_data.rssi(), Some(-70));
+        assert!(sensor_data.to_json().ends_with("\"rssi\":-70}"));
+    }
+
+    #[test]
+    fn test_ruuvitag_from_dbus_changed_properties_malformed_payload() {
+        let changed_properties = |payload: Box<dyn RefArg>| {
+            let mut manufacturer_data: HashMap<u16, Variant<Box<dyn RefArg>>> = HashMap::new();
+            manufacturer_data.insert(0x0499, Variant(payload));
+            let mut changed_properties: PropMap = HashMap::new();
+            changed_properties.insert(
+                "ManufacturerData".to_string(),
+                Variant(Box::new(manufacturer_data)),
+            );
+            changed_properties
+        };
+
+        let error = SensorDataV5::from_dbus_changed_properties(changed_properties(Box::new(vec![
+                0x05_u8;
+                25
+            ])))
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            RuuviError::WrongLength {
+                expected: 24,
+                got: 25
+            }
+        ));
+        let error = SensorDataV5::from_dbus_changed_properties(changed_properties(Box::new(
+            vec!["05".to_string(); 24],
+        )))
+        .unwrap_err();
+        assert!(matches!(error, RuuviError::Malformed(_)));
+        let error = SensorDataV5::from_dbus_changed_properties(changed_properties(Box::new(vec![
+            0x05_i32, 256,
+        ])))
+        .unwrap_err();
+        assert!(
+            matches!(error, RuuviError::Malformed(ref message) if message.contains("element 1"))
+        );
+    }
+
+    #[test]
+    fn test_ruuvitag_from_dbus_changed_properties_rssi_only() {
+        let mut changed_properties: PropMap = HashMap::new();
+        changed_properties.insert("RSSI".to_string(), Variant(Box::new(-70_i16)));
+
+        let error = SensorDataV5::from_dbus_changed_properties(changed_properties).unwrap_err();
+        assert!(matches!(
+            error,
+            RuuviError::MissingManufacturerData { ref found } if found.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_ruuvitag_from_dbus_changed_properties_without_ruuvi_manufacturer() {
+        let mut manufacturer_data: HashMap<u16, Variant<Box<dyn RefArg>>> = HashMap::new();
+        manufacturer_data.insert(0x004C, Variant(Box::new(vec![0x05_u8; 24])));
+        let mut changed_properties: PropMap = HashMap::new();
+        changed_properties.insert(
+            "ManufacturerData".to_string(),
+            Variant(Box::new(manufacturer_data)),
+        );
+
+        assert!(!has_ruuvi_manufacturer_data(&changed_properties));
+        assert!(!has_ruuvi_manufacturer_data(&PropMap::new()));
+
+        let error = SensorDataV5::from_dbus_changed_properties(changed_properties).unwrap_err();
+        assert!(matches!(
+            error,
+            RuuviError::MissingManufacturerData { ref found } if found == &["0x004C"]
+        ));
+    }
+
+    #[test]
+    fn test_ruuvitag_added_ruuvitag() {
+        let mut manufacturer_data: HashMap<u16, Variant<Box<dyn RefArg>>> = HashMap::new();
+        manufacturer_data.insert(0x0499, Variant(Box::new(vec![0x05_u8; 24])));
+        let mut properties: PropMap = HashMap::new();
+        properties.insert(
+            "ManufacturerData".to_string(),
+            Variant(Box::new(manufacturer_data)),
+        );
+        properties.insert(
+            "Address".to_string(),
+            Variant(Box::new("CB:B8:33:4C:88:4F".to_string())),
+        );
+        let added = |object: &str| ObjectManagerInterfacesAdded {
+            object: Path::from(object.to_string()),
+            interfaces: HashMap::from([(
+                "org.bluez.Device1".to_string(),
+                properties
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Variant(value.0.box_clone())))
+                    .collect(),
+            )]),
+        };
+
+        assert_eq!(
+            added_ruuvitag("hci0", &added("/org/bluez/hci0/dev_CB_B8_33_4C_88_4F"))
+                .map(|mac| mac.to_string())
+                .as_deref(),
+            Some("CB:B8:33:4C:88:4F")
+        );
+        assert_eq!(
+            added_ruuvitag("hci0", &added("/org/bluez/hci1/dev_CB_B8_33_4C_88_4F")),
+            None
+        );
+        assert_eq!(
+            added_ruuvitag("hci1", &added("/org/bluez/hci1/dev_CB_B8_33_4C_88_4F"))
+                .map(|mac| mac.to_string())
+                .as_deref(),
+            Some("CB:B8:33:4C:88:4F")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_takes_turns_between_tags() {
+        let (chatty_tx, chatty_rx) = channel(TAG_CHANNEL_CAPACITY);
+        let (quiet_tx, quiet_rx) = channel(TAG_CHANNEL_CAPACITY);
+        let (closed_tx, closed_rx) = channel::<&str>(TAG_CHANNEL_CAPACITY);
+        let mut readings = RoundRobin::new();
+        readings.push(chatty_rx);
+        readings.push(closed_rx);
+        readings.push(quiet_rx);
+        drop(closed_tx);
+        for reading in ["chatty 1", "chatty 2", "chatty 3"] {
+            chatty_tx.send(reading).await.unwrap();
+        }
+        quiet_tx.send("quiet 1").await.unwrap();
+        quiet_tx.send("quiet 2").await.unwrap();
+
+        // First in, first out within a tag, one reading per tag in turns across tags.
+        let mut received = Vec::new();
+        for _ in 0..5 {
+            received.push(readings.recv().await.unwrap());
+        }
+        assert_eq!(
+            received,
+            ["chatty 1", "quiet 1", "chatty 2", "quiet 2", "chatty 3"]
+        );
+        assert!(!readings.is_empty());
+
+        drop(chatty_tx);
+        drop(quiet_tx);
+        assert_eq!(readings.recv().await, None);
+        assert!(readings.is_empty());
+    }
+
+    /// Returns the changed properties of a signal carrying the Ruuvi manufacturer data `payload`.
+    fn manufacturer_data_properties(payload: Vec<u8>) -> PropMap {
+        let mut manufacturer_data: HashMap<u16, Variant<Box<dyn RefArg>>> = HashMap::new();
+        manufacturer_data.insert(0x0499, Variant(Box::new(payload)));
+        let mut changed_properties: PropMap = HashMap::new();
+        changed_properties.insert(
+            "ManufacturerData".to_string(),
+            Variant(Box::new(manufacturer_data)),
+        );
+        changed_properties
+    }
+
+    #[test]
+    fn test_tag_decoder_expected_format() {
+        let mac: MacAddress = "C0:CB:4E:3D:3E:12".parse().unwrap();
+        let v3 = vec![
+            0x03, 0x29, 0x1A, 0x1E, 0xCE, 0x1E, 0xFC, 0x18, 0xF9, 0x42, 0x02, 0xCA, 0x0B, 0x53,
+        ];
+
+        // Without an expected format the data format is detected from the payload.
+        let mut decoder = TagDecoder::new(mac, "hci0".to_string(), None);
+        let ruuvi_data = decoder
+            .decode(manufacturer_data_properties(v3.clone()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ruuvi_data.data_format(), 3);
+        let mut decoder = TagDecoder::new(mac, "hci0".to_string(), Some(3));
+        let ruuvi_data = decoder
+            .decode(manufacturer_data_properties(v3.clone()))
+            .unwrap()
+            .unwrap();
+        assert!(matches!(ruuvi_data, RuuviData::V3(_)));
+
+        let mut decoder = TagDecoder::new(mac, "hci0".to_string(), Some(5));
+        let error = decoder
+            .decode(manufacturer_data_properties(v3))
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            RuuviError::FormatMismatch { ref mac, expected: 5, got: 3 } if mac == "C0:CB:4E:3D:3E:12"
+        ));
+        assert!(matches!(
+            SensorDataV5::try_from(ruuvi_data),
+            Err(RuuviError::UnsupportedFormat(3))
+        ));
+
+        let mut rssi_only: PropMap = HashMap::new();
+        rssi_only.insert("RSSI".to_string(), Variant(Box::new(-70_i16)));
+        assert!(decoder.decode(rssi_only).is_none());
+    }
+
+    #[test]
+    fn test_tag_decoder_adapter_and_rssi() {
+        let payload = PayloadBuilder::spec_example().build().to_vec();
+        let mut decoder = TagDecoder::new(
+            "CB:B8:33:4C:88:4F".parse().unwrap(),
+            "hci1".to_string(),
+            None,
+        );
+        let sensor_data = |reading: Option<Result<RuuviData, RuuviError>>| {
+            SensorDataV5::try_from(reading.unwrap().unwrap()).unwrap()
+        };
+
+        let reading = sensor_data(decoder.decode(manufacturer_data_properties(payload.clone())));
+        assert_eq!(reading.adapter(), Some("hci1"));
+        assert_eq!(reading.rssi(), None);
+
+        // The RSSI changed without the data.
+        let mut rssi_only: PropMap = HashMap::new();
+        rssi_only.insert("RSSI".to_string(), Variant(Box::new(-70_i16)));
+        assert!(decoder.decode(rssi_only).is_none());
+        let reading = sensor_data(decoder.decode(manufacturer_data_properties(payload.clone())));
+        assert_eq!(reading.adapter(), Some("hci1"));
+        assert_eq!(reading.rssi(), Some(-70));
+
+        let mut changed_properties = manufacturer_data_properties(payload);
+        changed_properties.insert("RSSI".to_string(), Variant(Box::new(-60_i16)));
+        let reading = sensor_data(decoder.decode(changed_properties));
+        assert_eq!(reading.rssi(), Some(-60));
+    }
+
+    #[test]
+    fn test_tag_decoder_service_data() {
+        let mut frame = vec![0x10, 0xF6, 0x03];
+        frame.extend_from_slice(b"ruu.vi/#BHwYAMRnM");
+        let mut service_data = HashMap::new();
+        service_data.insert(
+            "0000feaa-0000-1000-8000-00805f9b34fb".to_string(),
+            Variant(Box::new(frame) as Box<dyn RefArg>),
+        );
+        let mut changed_properties: PropMap = HashMap::new();
+        changed_properties.insert("ServiceData".to_string(), Variant(Box::new(service_data)));
+
+        // A tag in URL mode sends only service data.
+        let mut decoder = TagDecoder::new(
+            "CC:6F:70:EE:4C:AD".parse().unwrap(),
+            "hci0".to_string(),
+            None,
+        );
+        let ruuvi_data = decoder.decode(changed_properties).unwrap().unwrap();
+        assert!(matches!(ruuvi_data, RuuviData::V2(_)));
+        assert_eq!(ruuvi_data.data_format(), 4);
+        assert_eq!(ruuvi_data.get_pressure(), 100279);
+        // Subscriptions sending only data format 5 report it instead of dropping it.
+        assert!(matches!(
+            SensorDataV5::try_from(ruuvi_data),
+            Err(RuuviError::UnsupportedFormat(4))
+        ));
+    }
+
+    #[test]
+    fn test_ruuvitag_from_raw_bytes_spec_example() {
+        // Valid data example of the ruuvi data format 5 specification.
+        let payload = [
+            0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC,
+            0x36, 0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F,
+        ];
+        let sensor_data = SensorDataV5::from_raw_bytes(&payload).unwrap();
+        assert_eq!(sensor_data.temperature_in_celcius(), 24.3);
+        assert!((sensor_data.temperature_in_fahrenheit() - 75.74).abs() < 1e-9);
+        assert_eq!(sensor_data.temperature_in_kelvin(), 297.45);
+        assert_eq!(
+            sensor_data.temperature_in(TemperatureUnit::Millicelsius),
+            24300.0
+        );
+        assert_eq!(
+            sensor_data.temperature_in(TemperatureUnit::Celsius),
+            sensor_data.temperature_in_celcius()
+        );
+        assert_eq!(sensor_data.get_humidity(), 53.49);
+        assert_eq!(sensor_data.get_pressure(), 100044);
+        assert_eq!(sensor_data.get_pressure_hpa(), 1000.44);
+        let acceleration = sensor_data.get_acceleration_in_mg();
+        assert_eq!(
+            (acceleration.x, acceleration.y, acceleration.z),
+            (4, -4, 1036)
+        );
+        assert_eq!(sensor_data.get_battery_voltage(), 2977);
+        assert_eq!(sensor_data.get_tx_power(), 4);
+        assert_eq!(sensor_data.movement_counter(), 66);
+        assert_eq!(sensor_data.measurement_number(), 205);
+        assert_eq!(sensor_data.mac_as_str(), "CB:B8:33:4C:88:4F");
+        assert!(SensorDataV5::from_raw_bytes(&payload[..23]).is_err());
+    }
+
+    #[test]
+    fn test_ruuvitag_verify_mac() {
+        let sensor_data = SensorDataV5::new(
+            0,
+            0,
+            0,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F],
+        );
+        assert!(sensor_data
+            .verify_mac(&"cb:b8:33:4c:88:4f".parse().unwrap())
+            .is_ok());
+        assert!(matches!(
+            sensor_data.verify_mac(&"CC:6F:70:EE:4C:AD".parse().unwrap()),
+            Err(RuuviError::MacMismatch { expected, got })
+                if expected == "CC:6F:70:EE:4C:AD" && got == "CB:B8:33:4C:88:4F"
+        ));
+    }
+
+    #[test]
+    fn test_ruuvitag_from_raw_bytes_unexpected_format() {
+        let mut payload = [0_u8; 24];
+        payload[0] = 0x03;
+        assert!(SensorDataV5::from_raw_bytes(&payload).is_err());
+        payload[0] = 0x05;
+        assert!(SensorDataV5::from_raw_bytes(&payload).is_ok());
+    }
+
+    #[test]
+    fn test_ruuvitag_enthalpy_at_standard_conditions() {
+        // 20 °C, 50 % and 101325 Pa.
+        let sensor_data = SensorDataV5::new(
+            4000,
+            20000,
+            51325,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert!((sensor_data.enthalpy_kj_kg() - 38.5).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_ruuvitag_to_raw_bytes_round_trip() {
+        let payload = [
+            0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC,
+            0x36, 0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F,
+        ];
+        let sensor_data = SensorDataV5::from_raw_bytes(&payload).unwrap();
+        assert_eq!(sensor_data.to_raw_bytes(), payload);
+    }
+
+    #[test]
+    fn test_ruuvitag_humidity_above_saturation() {
+        // 40120 / 400 = 100.3 %
+        let sensor_data = SensorDataV5::new(
+            0,
+            40120,
+            0,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert_eq!(sensor_data.get_humidity(), 100.3);
+        assert_eq!(sensor_data.get_humidity_clamped(), 100.0);
+    }
+
+    #[test]
+    fn test_ruuvitag_vapor_pressure() {
+        // 20 °C and 50 %, saturation vapor pressure is ~2339 Pa.
+        let sensor_data = SensorDataV5::new(
+            4000,
+            20000,
+            51325,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert!((sensor_data.vapor_pressure_pa() - 1169.0).abs() < 5.0);
+        assert!((sensor_data.absolute_humidity().unwrap() - 8.65).abs() < 0.05);
+        assert!((sensor_data.dew_point_celsius().unwrap() - 9.26).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_ruuvitag_dew_point_and_absolute_humidity() {
+        // 24.3 °C and 53.5 %.
+        let sensor_data = SensorDataV5::new(
+            4860,
+            21400,
+            0,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert!((sensor_data.dew_point_celsius().unwrap() - 14.3).abs() < 0.1);
+        // Saturation vapor pressure at 24.3 °C is ~3031 Pa.
+        assert!((sensor_data.absolute_humidity().unwrap() - 11.81).abs() < 0.05);
+
+        let sensor_data = SensorDataV5::new(
+            INVALID_TEMPERATURE,
+            21400,
+            0,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert_eq!(sensor_data.dew_point_celsius(), None);
+        assert_eq!(sensor_data.absolute_humidity(), None);
+
+        let sensor_data = SensorDataV5::new(
+            4860,
+            INVALID_HUMIDITY,
+            0,
+            Acceleration::new(0, 0, 0),
+            0,
+            0,
+            0,
+            [0, 0, 0, 0, 0, 0],
+        );
+        assert_eq!(sensor_data.dew_point_celsius(), None);
+        assert_eq!(sensor_data.absolute_humidity(), None);
+    }
+
+    #[test]
+    fn test_ruuvitag_sensor_data_eq_hash() {
+        let sensor_data = SensorDataV5::new(
+            4300,
+            18000,
+            50300,
+            Acceleration::new(0, 0, 1000),
+            1300 << 5,
+            0,
+            0,
+            [0xCC, 0x6F, 0x70, 0xEE, 0x4C, 0xAD],
+        );
+        let mut next = sensor_data.clone();
+        assert_eq!(next, sensor_data);
+        next.measurement_number += 1;
+        assert_ne!(next, sensor_data);
+
+        let acceleration = *sensor_data.get_acceleration_in_mg();
+        assert_eq!(acceleration, Acceleration::new(0, 0, 1000));
+
+        // The same payload heard by another adapter at another RSSI.
+        let mut heard = sensor_data.clone();
+        heard.adapter = Some("hci1".to_string());
+        heard.rssi = Some(-70);
+        assert_eq!(heard, sensor_data);
+
+        let readings: HashSet<SensorDataV5> = [sensor_data.clone(), next, sensor_data, heard]
+            .into_iter()
+            .collect();
+        assert_eq!(readings.len(), 2);
+    }
+
+    #[test]
+    fn test_decode_power_info() {
+        const MIN: (u16, i8) = (decode_battery_voltage(0), decode_tx_power(0));
+        assert_eq!(MIN, (BATTERY_OFFSET, TX_POWER_OFFSET));
+        assert_eq!(decode_battery_voltage(0xAC36), 2977);
+        assert_eq!(decode_tx_power(0xAC36), 4);
+        assert_eq!(decode_battery_voltage(u16::MAX), 3647);
+        assert_eq!(decode_tx_power(u16::MAX), 22);
+    }
+
+    #[test]
+    fn test_ruuvitag_summary() {
+        let sensor_data = SensorDataV5::new(
+            4300,
+            18000,
+            50300,
+            Acceleration::new(0, 0, 1000),
+            1300 << 5,
+            0,
+            0,
+            [0xCC, 0x6F, 0x70, 0xEE, 0x4C, 0xAD],
+        );
+        assert_eq!(
+            sensor_data.summary(),
+            "CC:6F:70:EE:4C:AD 21.5°C 45% 1003hPa bat=2900mV"
+        );
+        assert_eq!(sensor_data.to_string(), sensor_data.summary());
+        let verbose = format!("{sensor_data:#}");
+        assert!(verbose.starts_with("MAC address: \"CC:6F:70:EE:4C:AD\"\n"));
+        assert!(verbose.ends_with("RSSI (dBm): None\n"));
+        let mut written = Vec::new();
+        sensor_data.write_sensor_data(&mut written).unwrap();
+        assert_eq!(written, format!("{verbose}\n").into_bytes());
+    }
+
+    #[test]
+    fn test_ruuvitag_sensor_data_v3() {
+        // Test vectors of the ruuvi data format 3 specification.
+        let valid = [
+            0x03, 0x29, 0x1A, 0x1E, 0xCE, 0x1E, 0xFC, 0x18, 0xF9, 0x42, 0x02, 0xCA, 0x0B, 0x53,
+        ];
+        let sensor_data = SensorDataV3::from_raw_bytes(&valid).unwrap();
+        assert_eq!(sensor_data.temperature_in_celcius(), 26.3);
+        assert_eq!(sensor_data.get_humidity(), 20.5);
+        assert_eq!(sensor_data.get_pressure(), 102766);
+        let acceleration = sensor_data.get_acceleration_in_mg();
+        assert_eq!(
+            (acceleration.x, acceleration.y, acceleration.z),
+            (-1000, -1726, 714)
+        );
+        assert_eq!(sensor_data.get_battery_voltage(), 2899);
+
+        let min = [
+            0x03, 0x00, 0xFF, 0x63, 0x00, 0x00, 0x80, 0x01, 0x80, 0x01, 0x80, 0x01, 0x00, 0x00,
+        ];
+        let sensor_data = SensorDataV3::from_raw_bytes(&min).unwrap();
+        assert_eq!(sensor_data.temperature_in_millicelcius(), -127990);
+        assert_eq!(sensor_data.get_humidity(), 0.0);
+        assert_eq!(sensor_data.get_pressure(), 50000);
+
+        assert!(SensorDataV3::from_raw_bytes(&valid[..13]).is_err());
+        assert!(SensorDataV3::from_raw_bytes(&[0x05; 14]).is_err());
+    }
+
+    #[test]
+    fn test_ruuvitag_sensor_data_v2() {
+        let sensor_data = SensorDataV2::from_url("https://ruu.vi/#AjwYAMFc").unwrap();
+        assert_eq!(sensor_data.data_format(), 2);
+        assert_eq!(sensor_data.temperature_in_celcius(), 24.0);
+        assert_eq!(sensor_data.get_humidity(), 30.0);
+        assert_eq!(sensor_data.get_pressure(), 99500);
+
+        let mut frame = vec![0x10, 0xF6, 0x03];
+        frame.extend_from_slice(b"ruu.vi/#BHwYAMRnM");
+        let sensor_data = SensorDataV2::from_eddystone_url_frame(&frame).unwrap();
+        assert_eq!(sensor_data.data_format(), 4);
+        assert_eq!(sensor_data.get_humidity(), 62.0);
+        assert_eq!(sensor_data.get_pressure(), 100279);
+
+        let mut service_data = HashMap::new();
+        service_data.insert(
+            "0000feaa-0000-1000-8000-00805f9b34fb".to_string(),
+            Variant(Box::new(frame) as Box<dyn RefArg>),
+        );
+        let mut changed_properties: PropMap = HashMap::new();
+        changed_properties.insert("ServiceData".to_string(), Variant(Box::new(service_data)));
+        let sensor_data = SensorDataV2::from_dbus_changed_properties(changed_properties).unwrap();
+        assert_eq!(sensor_data.temperature_in_celcius(), 24.0);
+
+        assert!(SensorDataV2::from_url("https://ruu.vi/").is_err());
+        assert!(SensorDataV2::from_url("https://ruu.vi/#BRL8U5TD").is_err());
+    }
+
+    #[test]
+    fn test_ruuvitag_decode() {
+        let v3 = [
+            0x03, 0x29, 0x1A, 0x1E, 0xCE, 0x1E, 0xFC, 0x18, 0xF9, 0x42, 0x02, 0xCA, 0x0B, 0x53,
+        ];
+        let ruuvi_data = decode(&v3).unwrap();
+        assert!(matches!(ruuvi_data, RuuviData::V3(_)));
+        assert_eq!(ruuvi_data.data_format(), 3);
+        assert_eq!(ruuvi_data.temperature_in_celcius(), 26.3);
+
+        let v5 = [
+            0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC,
+            0x36, 0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F,
+        ];
+        let ruuvi_data = decode(&v5).unwrap();
+        assert!(matches!(ruuvi_data, RuuviData::V5(_)));
+        assert_eq!(ruuvi_data.data_format(), 5);
+        assert_eq!(ruuvi_data.get_pressure(), 100044);
+
+        assert!(decode(&[0x06, 0x00]).is_err());
+        assert!(decode(&[]).is_err());
+    }
+
+    #[test]
+    fn test_acceleration_conversions_round_trip() {
+        let array: [i16; 3] = Acceleration::from([4, -4, 1036]).into();
+        assert_eq!(array, [4, -4, 1036]);
+
+        let tuple: (i16, i16, i16) = Acceleration::from((i16::MIN, 0, i16::MAX)).into();
+        assert_eq!(tuple, (i16::MIN, 0, i16::MAX));
+
+        let acceleration = Acceleration::from([1, 2, 3]);
+        assert_eq!((acceleration.x, acceleration.y, acceleration.z), (1, 2, 3));
+    }
+
+    #[test]
+    fn test_acceleration_ops() {
+        let previous = Acceleration::new(4, -4, 1036);
+        let current = Acceleration::from([10, -20, 1000]);
+        assert_eq!(current - previous, Acceleration::new(6, -16, -36));
+        assert_eq!(previous + (current - previous), current);
+        assert_eq!(
+            (current[0], current[1], current[2]),
+            (current.x, current.y, current.z)
+        );
+
+        let edge = Acceleration::new(i16::MAX, -i16::MAX, 0);
+        assert_eq!(
+            edge + Acceleration::new(1, -1, i16::MAX),
+            Acceleration::new(i16::MAX, -i16::MAX, i16::MAX)
+        );
+        assert_eq!(
+            edge - Acceleration::new(-1, 1, i16::MAX),
+            Acceleration::new(i16::MAX, -i16::MAX, -i16::MAX)
+        );
+
+        let invalid = Acceleration::new(INVALID_ACCELERATION, 1, 2);
+        assert_eq!((invalid - previous).try_x(), None);
+        assert_eq!((previous + invalid).try_x(), None);
+        assert_eq!((previous + invalid).try_y(), Some(-3));
+    }
+
+    #[test]
+    #[should_panic(expected = "acceleration has 3 axes but the index is 3")]
+    fn test_acceleration_index_out_of_bounds() {
+        let _ = Acceleration::new(0, 0, 0)[3];
+    }
+
+    #[test]
+    fn test_acceleration_magnitude_and_in_g() {
+        let acceleration = Acceleration::new(0, -600, 800);
+        assert_eq!(acceleration.magnitude_mg(), 1000.0);
+        assert_eq!(acceleration.in_g(), (0.0, -0.6, 0.8));
+    }
+
+    #[test]
+    fn test_acceleration_free_fall_and_impact() {
+        let at_rest = Acceleration::new(4, -4, 1036);
+        assert!(!at_rest.is_free_fall(DEFAULT_FREE_FALL_THRESHOLD_MG));
+        assert!(!at_rest.is_impact(DEFAULT_IMPACT_THRESHOLD_MG));
+
+        let falling = Acceleration::new(20, -50, 120);
+        assert!(falling.is_free_fall(DEFAULT_FREE_FALL_THRESHOLD_MG));
+        assert!(!falling.is_impact(DEFAULT_IMPACT_THRESHOLD_MG));
+
+        let impact = Acceleration::new(-2500, 1800, 900);
+        assert!(!impact.is_free_fall(DEFAULT_FREE_FALL_THRESHOLD_MG));
+        assert!(impact.is_impact(DEFAULT_IMPACT_THRESHOLD_MG));
+
+        let invalid = Acceleration::new(INVALID_ACCELERATION, 0, 0);
+        assert!(!invalid.is_free_fall(DEFAULT_FREE_FALL_THRESHOLD_MG));
+        assert!(!invalid.is_impact(DEFAULT_IMPACT_THRESHOLD_MG));
+    }
+
+    #[test]
+    fn test_acceleration_orientation() {
+        assert_eq!(
+            Acceleration::new(0, 0, 1000).orientation(),
+            Orientation::FaceUp
+        );
+        assert_eq!(
+            Acceleration::new(40, -30, -980).orientation(),
+            Orientation::FaceDown
+        );
+        assert_eq!(
+            Acceleration::new(-1036, 4, -4).orientation(),
+            Orientation::OnSide {
+                axis: Axis::X,
+                positive: false
+            }
+        );
+        assert_eq!(
+            Acceleration::new(0, 707, 707).orientation(),
+            Orientation::Unknown
+        );
+        assert_eq!(
+            Acceleration::new(0, 707, 700).orientation_with_tolerance(300),
+            Orientation::OnSide {
+                axis: Axis::Y,
+                positive: true
+            }
+        );
+        assert_eq!(
+            Acceleration::new(0, INVALID_ACCELERATION, 1000).orientation(),
+            Orientation::Unknown
         );
-        sensor_data.temperature_in_millicelcius();
     }
 }
@@ -0,0 +1,651 @@
+//! Module to assist in computing statistics over ruuvitag readings.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::ruuvitag::subscribe_ruuvitag;
+//! use ruuviscanner::stats::NoiseEstimator;
+//!
+//! // Replace with your mac address.
+//! let mac = "CC:6F:70:EE:4C:AD";
+//! let mut rx = subscribe_ruuvitag(mac).await?;
+//! let mut noise = NoiseEstimator::new(0.1);
+//! loop {
+//!     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+//!     noise.push(&current_sensor_data);
+//!     println!("{:?}", noise.temperature_std());
+//! }
+//! ```
+use crate::ruuvitag::{SensorDataV5, SensorField};
+use std::collections::HashMap;
+
+/// Number of samples required before a standard deviation is reported.
+const MIN_SAMPLES: u32 = 2;
+
+/// Exponentially weighted moving estimate of the mean and variance of a value.
+#[derive(Debug, Clone, Copy)]
+struct EwmVariance {
+    alpha: f64,
+    mean: f64,
+    variance: f64,
+    count: u32,
+}
+
+impl EwmVariance {
+    fn new(alpha: f64) -> Self {
+        Self {
+            alpha,
+            mean: 0_f64,
+            variance: 0_f64,
+            count: 0,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if self.count == 0 {
+            self.mean = value;
+        } else {
+            let diff = value - self.mean;
+            let increment = self.alpha * diff;
+            self.mean += increment;
+            self.variance = (1_f64 - self.alpha) * (self.variance + diff * increment);
+        }
+        self.count = self.count.saturating_add(1);
+    }
+
+    fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    fn std(&self) -> Option<f64> {
+        if self.count < MIN_SAMPLES {
+            return None;
+        }
+        Some(self.variance.sqrt())
+    }
+}
+
+/// Minimum, maximum and mean of a value.
+#[derive(Debug, Clone, Copy, Default)]
+struct Summary {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: u64,
+}
+
+impl Summary {
+    fn push(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn range(&self) -> Option<(f64, f64)> {
+        (self.count > 0).then_some((self.min, self.max))
+    }
+
+    fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+/// Minimum, maximum and mean of the temperature, humidity and pressure over a session.
+///
+/// Invalid values are ignored, so a single reading reported as not available doesn't affect the
+/// summary. The getters return `None` until a valid value of the metric has been pushed.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::subscribe_ruuvitag;
+/// use ruuviscanner::stats::Stats;
+///
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut rx = subscribe_ruuvitag(mac).await?;
+/// let mut daily = Stats::new();
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     daily.push(&current_sensor_data);
+///     println!("{:?} {:?}", daily.temperature_range(), daily.temperature_mean());
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    temperature: Summary,
+    humidity: Summary,
+    pressure: Summary,
+}
+
+impl Stats {
+    /// Constructs an empty `Stats`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a reading to the accumulator.
+    pub fn push(&mut self, sensor_data: &SensorDataV5) {
+        if let Some(temperature) = sensor_data.try_temperature_in_celcius() {
+            self.temperature.push(temperature);
+        }
+        if let Some(humidity) = sensor_data.try_get_humidity() {
+            self.humidity.push(humidity);
+        }
+        if let Some(pressure) = sensor_data.try_get_pressure() {
+            self.pressure.push(pressure as f64);
+        }
+    }
+
+    /// Returns the minimum and maximum temperature in celsius.
+    pub fn temperature_range(&self) -> Option<(f64, f64)> {
+        self.temperature.range()
+    }
+
+    /// Returns the mean temperature in celsius.
+    pub fn temperature_mean(&self) -> Option<f64> {
+        self.temperature.mean()
+    }
+
+    /// Returns the minimum and maximum humidity in %.
+    pub fn humidity_range(&self) -> Option<(f64, f64)> {
+        self.humidity.range()
+    }
+
+    /// Returns the mean humidity in %.
+    pub fn humidity_mean(&self) -> Option<f64> {
+        self.humidity.mean()
+    }
+
+    /// Returns the minimum and maximum pressure in Pa.
+    pub fn pressure_range(&self) -> Option<(f64, f64)> {
+        self.pressure.range()
+    }
+
+    /// Returns the mean pressure in Pa.
+    pub fn pressure_mean(&self) -> Option<f64> {
+        self.pressure.mean()
+    }
+}
+
+/// Running estimate of the noise of the temperature, humidity and pressure of a ruuvitag.
+///
+/// Uses the exponentially weighted moving variance, where `alpha` (0..=1) is the weight of the
+/// newest reading. A larger `alpha` follows changes in the noise faster, a smaller one gives a
+/// steadier estimate. Invalid values are ignored. The estimates help in choosing anomaly and
+/// deadband thresholds.
+#[derive(Debug, Clone)]
+pub struct NoiseEstimator {
+    temperature: EwmVariance,
+    humidity: EwmVariance,
+    pressure: EwmVariance,
+}
+
+impl NoiseEstimator {
+    /// Constructs a `NoiseEstimator` with the weight `alpha` of the newest reading.
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            temperature: EwmVariance::new(alpha),
+            humidity: EwmVariance::new(alpha),
+            pressure: EwmVariance::new(alpha),
+        }
+    }
+
+    /// Feeds a reading to the estimator.
+    pub fn push(&mut self, sensor_data: &SensorDataV5) {
+        if sensor_data.is_valid(SensorField::Temperature) {
+            self.temperature.push(sensor_data.temperature_in_celcius());
+        }
+        if sensor_data.is_valid(SensorField::Humidity) {
+            self.humidity.push(sensor_data.get_humidity());
+        }
+        if sensor_data.is_valid(SensorField::Pressure) {
+            self.pressure.push(sensor_data.get_pressure() as f64);
+        }
+    }
+
+    /// Returns the standard deviation of the temperature in celsius.
+    ///
+    /// `None` until at least two valid temperatures have been pushed.
+    pub fn temperature_std(&self) -> Option<f64> {
+        self.temperature.std()
+    }
+
+    /// Returns the standard deviation of the humidity in %.
+    ///
+    /// `None` until at least two valid humidities have been pushed.
+    pub fn humidity_std(&self) -> Option<f64> {
+        self.humidity.std()
+    }
+
+    /// Returns the standard deviation of the pressure in Pa.
+    ///
+    /// `None` until at least two valid pressures have been pushed.
+    pub fn pressure_std(&self) -> Option<f64> {
+        self.pressure.std()
+    }
+}
+
+/// Exponentially weighted moving average of the temperature, humidity and pressure of a
+/// ruuvitag.
+///
+/// Smooths the jitter between consecutive readings, e.g. for a display. `alpha` (0..=1) is the
+/// weight of the newest reading, a smaller `alpha` gives smoother values that lag further behind
+/// real changes. Invalid values are ignored, so a reading with e.g. an unavailable humidity still
+/// updates the temperature and pressure.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::subscribe_ruuvitag;
+/// use ruuviscanner::stats::Ewma;
+///
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut rx = subscribe_ruuvitag(mac).await?;
+/// let mut smoothed = Ewma::new(0.2);
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     smoothed.push(&current_sensor_data);
+///     println!("{:?}", smoothed.temperature());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ewma {
+    temperature: EwmVariance,
+    humidity: EwmVariance,
+    pressure: EwmVariance,
+}
+
+impl Ewma {
+    /// Constructs an `Ewma` with the weight `alpha` of the newest reading.
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            temperature: EwmVariance::new(alpha),
+            humidity: EwmVariance::new(alpha),
+            pressure: EwmVariance::new(alpha),
+        }
+    }
+
+    /// Feeds a reading to the average.
+    pub fn push(&mut self, sensor_data: &SensorDataV5) {
+        if let Some(temperature) = sensor_data.try_temperature_in_celcius() {
+            self.temperature.push(temperature);
+        }
+        if let Some(humidity) = sensor_data.try_get_humidity() {
+            self.humidity.push(humidity);
+        }
+        if let Some(pressure) = sensor_data.try_get_pressure() {
+            self.pressure.push(pressure as f64);
+        }
+    }
+
+    /// Returns the smoothed temperature in celsius, `None` until a valid temperature has been
+    /// pushed.
+    pub fn temperature(&self) -> Option<f64> {
+        self.temperature.mean()
+    }
+
+    /// Returns the smoothed humidity in %, `None` until a valid humidity has been pushed.
+    pub fn humidity(&self) -> Option<f64> {
+        self.humidity.mean()
+    }
+
+    /// Returns the smoothed pressure in Pa, `None` until a valid pressure has been pushed.
+    pub fn pressure(&self) -> Option<f64> {
+        self.pressure.mean()
+    }
+}
+
+/// Result of feeding a measurement number to a [`SequenceTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceGap {
+    /// The first measurement number fed to the tracker, there is nothing to compare against.
+    First,
+    /// The same measurement number as the previous one. Ruuvitags advertise each measurement
+    /// several times, so repeats are expected.
+    Repeated,
+    /// The measurement number following the previous one.
+    Consecutive,
+    /// Number of measurements skipped since the previous one.
+    Dropped(u16),
+}
+
+/// Detects dropped advertisements from the measurement numbers of a ruuvitag.
+///
+/// The measurement number of data format 5 increments with every measurement and wraps around
+/// from 65535 to 0, so a jump over more than one number means the advertisements in between were
+/// missed, e.g. due to interference or range. A tag that restarts begins counting from 0 again,
+/// which shows up as a single large gap.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::subscribe_ruuvitag;
+/// use ruuviscanner::stats::{SequenceGap, SequenceTracker};
+///
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut rx = subscribe_ruuvitag(mac).await?;
+/// let mut sequence = SequenceTracker::new();
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     let gap = sequence.observe(current_sensor_data.measurement_number());
+///     if let SequenceGap::Dropped(dropped) = gap {
+///         println!("{dropped} dropped, {} in total", sequence.dropped());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    last: Option<u16>,
+    dropped: u64,
+}
+
+impl SequenceTracker {
+    /// Constructs a `SequenceTracker` that hasn't seen any measurement numbers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the measurement number `n` of the next reading of the tracked ruuvitag.
+    pub fn observe(&mut self, n: u16) -> SequenceGap {
+        let gap = match self.last {
+            None => SequenceGap::First,
+            Some(last) if n == last => SequenceGap::Repeated,
+            Some(last) => match n.wrapping_sub(last) - 1 {
+                0 => SequenceGap::Consecutive,
+                skipped => SequenceGap::Dropped(skipped),
+            },
+        };
+        if let SequenceGap::Dropped(skipped) = gap {
+            self.dropped += u64::from(skipped);
+        }
+        self.last = Some(n);
+        gap
+    }
+
+    /// Returns the total number of measurements skipped so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Counts movement events from the movement counter of a ruuvitag.
+///
+/// The movement counter of data format 5 increments every time the accelerometer detects
+/// motion and wraps around from 255 to 0. More than 255 movements between two received
+/// readings can't be told apart from fewer, so the readings should arrive frequently enough for
+/// the expected activity.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::ruuvitag::subscribe_ruuvitag;
+/// use ruuviscanner::stats::MovementTracker;
+///
+/// // Replace with your mac address.
+/// let mac = "CC:6F:70:EE:4C:AD";
+/// let mut rx = subscribe_ruuvitag(mac).await?;
+/// let mut door = MovementTracker::new();
+/// loop {
+///     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+///     if door.observe(current_sensor_data.movement_counter()) > 0 {
+///         println!("Door moved, {} times in total", door.total());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MovementTracker {
+    last: Option<u8>,
+    total: u64,
+}
+
+impl MovementTracker {
+    /// Constructs a `MovementTracker` that hasn't seen any movement counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the movement counter of the next reading of the tracked ruuvitag and returns the
+    /// number of movements since the previous reading.
+    ///
+    /// Returns 0 for the first reading, as there is nothing to compare against.
+    pub fn observe(&mut self, counter: u8) -> u8 {
+        let movements = self.last.map_or(0, |last| counter.wrapping_sub(last));
+        self.total += u64::from(movements);
+        self.last = Some(counter);
+        movements
+    }
+
+    /// Returns the total number of movements counted so far.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+/// Quality summary of a batch of payloads returned by [`analyze_batch`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchQuality {
+    /// Number of payloads in the batch.
+    pub total: usize,
+    /// Number of payloads decoded into sensor data.
+    pub decoded: usize,
+    /// Number of decoded payloads with plausible values, see [`SensorDataV5::is_plausible`].
+    pub plausible: usize,
+    /// Number of skipped measurement numbers between consecutive readings of the same tag.
+    pub sequence_gaps: usize,
+}
+
+impl BatchQuality {
+    /// Returns the fraction (0...1) of the payloads that were decoded, 0 for an empty batch.
+    pub fn decoded_fraction(&self) -> f64 {
+        fraction(self.decoded, self.total)
+    }
+
+    /// Returns the fraction (0...1) of the decoded payloads that were plausible, 0 if none were
+    /// decoded.
+    pub fn plausible_fraction(&self) -> f64 {
+        fraction(self.plausible, self.decoded)
+    }
+}
+
+fn fraction(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0_f64;
+    }
+    part as f64 / total as f64
+}
+
+/// Decodes a batch of raw 24 byte payloads and summarizes its quality.
+///
+/// Sequence gaps are counted per tag from the measurement numbers of consecutive decoded
+/// readings, so the payloads are expected in arrival order. Repeats of the same measurement
+/// number are not gaps.
+///
+/// # Examples
+///
+/// ```
+/// use ruuviscanner::stats::analyze_batch;
+///
+/// let payloads = vec![[
+///     0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC, 0x36,
+///     0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F,
+/// ]];
+/// let quality = analyze_batch(&payloads);
+/// assert_eq!(quality.decoded_fraction(), 1.0);
+/// ```
+pub fn analyze_batch<I>(payloads: I) -> BatchQuality
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut quality = BatchQuality::default();
+    let mut sequences: HashMap<String, SequenceTracker> = HashMap::new();
+    for payload in payloads {
+        quality.total += 1;
+        let sensor_data = match SensorDataV5::from_raw_bytes(payload.as_ref()) {
+            Ok(sensor_data) => sensor_data,
+            Err(_) => continue,
+        };
+        quality.decoded += 1;
+        if sensor_data.is_plausible() {
+            quality.plausible += 1;
+        }
+        let sequence = sequences.entry(sensor_data.mac_as_str()).or_default();
+        if let SequenceGap::Dropped(skipped) = sequence.observe(sensor_data.measurement_number()) {
+            quality.sequence_gaps += skipped as usize;
+        }
+    }
+    quality
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::ruuvitag::SensorField;
+    use crate::stats::{
+        analyze_batch, BatchQuality, Ewma, MovementTracker, NoiseEstimator, SequenceGap,
+        SequenceTracker, Stats,
+    };
+    use crate::testing::PayloadBuilder;
+
+    #[test]
+    fn test_noise_estimator() {
+        let mut noise = NoiseEstimator::new(0.1);
+        assert_eq!(noise.temperature_std(), None);
+        noise.push(
+            &PayloadBuilder::spec_example()
+                .temperature(20.0)
+                .sensor_data(),
+        );
+        assert_eq!(noise.temperature_std(), None);
+        for celsius in [20.0, 20.1, 19.9, 20.0, 20.1, 19.9] {
+            noise.push(
+                &PayloadBuilder::spec_example()
+                    .temperature(celsius)
+                    .sensor_data(),
+            );
+        }
+        let temperature_std = noise.temperature_std().unwrap();
+        assert!(temperature_std > 0.0 && temperature_std < 0.1);
+        assert_eq!(noise.humidity_std(), Some(0.0));
+        // Invalid temperatures are ignored.
+        noise.push(
+            &PayloadBuilder::spec_example()
+                .not_available(SensorField::Temperature)
+                .sensor_data(),
+        );
+        assert!(noise.temperature_std().unwrap() < 0.1);
+    }
+
+    #[test]
+    fn test_ewma() {
+        let mut smoothed = Ewma::new(0.5);
+        assert_eq!(smoothed.temperature(), None);
+        // 20 °C, then 22 °C.
+        smoothed.push(
+            &PayloadBuilder::spec_example()
+                .temperature(20.0)
+                .sensor_data(),
+        );
+        assert_eq!(smoothed.temperature(), Some(20.0));
+        smoothed.push(
+            &PayloadBuilder::spec_example()
+                .temperature(22.0)
+                .sensor_data(),
+        );
+        assert_eq!(smoothed.temperature(), Some(21.0));
+        // Invalid temperatures are ignored.
+        smoothed.push(
+            &PayloadBuilder::spec_example()
+                .not_available(SensorField::Temperature)
+                .sensor_data(),
+        );
+        assert_eq!(smoothed.temperature(), Some(21.0));
+        assert_eq!(smoothed.humidity(), Some(53.49));
+        assert_eq!(smoothed.pressure(), Some(100044.0));
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut stats = Stats::new();
+        assert_eq!(stats.temperature_range(), None);
+        assert_eq!(stats.temperature_mean(), None);
+        // 20, 22 and 18 °C.
+        for celsius in [20.0, 22.0, 18.0] {
+            stats.push(
+                &PayloadBuilder::spec_example()
+                    .temperature(celsius)
+                    .sensor_data(),
+            );
+        }
+        stats.push(
+            &PayloadBuilder::spec_example()
+                .not_available(SensorField::Temperature)
+                .sensor_data(),
+        );
+        assert_eq!(stats.temperature_range(), Some((18.0, 22.0)));
+        assert_eq!(stats.temperature_mean(), Some(20.0));
+        assert_eq!(stats.humidity_range(), Some((53.49, 53.49)));
+        assert_eq!(stats.pressure_mean(), Some(100044.0));
+    }
+
+    #[test]
+    fn test_sequence_tracker() {
+        let mut sequence = SequenceTracker::new();
+        assert_eq!(sequence.observe(65533), SequenceGap::First);
+        assert_eq!(sequence.observe(65533), SequenceGap::Repeated);
+        assert_eq!(sequence.observe(65534), SequenceGap::Consecutive);
+        assert_eq!(sequence.observe(1), SequenceGap::Dropped(2));
+        assert_eq!(sequence.observe(2), SequenceGap::Consecutive);
+        assert_eq!(sequence.observe(10), SequenceGap::Dropped(7));
+        assert_eq!(sequence.dropped(), 9);
+    }
+
+    #[test]
+    fn test_movement_tracker() {
+        let mut movements = MovementTracker::new();
+        assert_eq!(movements.observe(250), 0);
+        assert_eq!(movements.observe(250), 0);
+        assert_eq!(movements.observe(253), 3);
+        assert_eq!(movements.observe(2), 5);
+        assert_eq!(movements.total(), 8);
+    }
+
+    #[test]
+    fn test_analyze_batch() {
+        let payload = |celsius: f64, measurement_number: u16| {
+            PayloadBuilder::spec_example()
+                .temperature(celsius)
+                .measurement_number(measurement_number)
+                .build()
+                .to_vec()
+        };
+        let payloads = vec![
+            payload(20.0, 1),
+            payload(20.0, 1),
+            payload(20.0, 2),
+            payload(100.0, 5),
+            vec![0x05, 0x12],
+        ];
+        let quality = analyze_batch(&payloads);
+        assert_eq!(
+            quality,
+            BatchQuality {
+                total: 5,
+                decoded: 4,
+                plausible: 3,
+                sequence_gaps: 2,
+            }
+        );
+        assert_eq!(quality.decoded_fraction(), 0.8);
+        assert_eq!(quality.plausible_fraction(), 0.75);
+        assert_eq!(analyze_batch(Vec::<Vec<u8>>::new()).decoded_fraction(), 0.0);
+    }
+}
@@ -0,0 +1,254 @@
+//! Module to assist in testing code that handles ruuvitag data.
+//!
+//! Available with the `testing` feature.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::testing::PayloadBuilder;
+//!
+//! let payload: [u8; 24] = PayloadBuilder::new()
+//!     .temperature(24.3)
+//!     .humidity(53.49)
+//!     .pressure(100044)
+//!     .build();
+//! ```
+use crate::ruuvitag::{
+    Acceleration, SensorDataV5, SensorField, BATTERY_OFFSET, INVALID_ACCELERATION,
+    INVALID_HUMIDITY, INVALID_PRESSURE, INVALID_TEMPERATURE, TX_POWER_OFFSET,
+};
+
+/// Offset of the pressure field in Pa.
+const PRESSURE_OFFSET_PA: u32 = 50000;
+/// Largest valid value of the 11 bit battery voltage field, `0x7FF` marks it not available.
+const MAX_BATTERY: u16 = 0x7FE;
+/// Largest valid value of the 5 bit transmit power field, `0x1F` marks it not available.
+const MAX_TX_POWER: i16 = 0x1E;
+
+/// Builds spec compliant data format 5 payloads from physical values.
+///
+/// Takes care of the scaling and packing of the values so the resulting 24 byte payload decodes
+/// back to the given values (within the resolution of the format). Values that are not set are
+/// encoded as zeros.
+///
+/// Values outside of the range of the format are clamped to the nearest value in range. The
+/// format reserves the extremes of the fields for marking a value not available, so they are
+/// left out of the range and can only be set with `not_available`.
+#[derive(Debug, Default, Clone)]
+pub struct PayloadBuilder {
+    temperature: i16,
+    humidity: u16,
+    pressure: u16,
+    acceleration: (i16, i16, i16),
+    battery: u16,
+    tx_power: u16,
+    movement_counter: u8,
+    measurement_number: u16,
+    mac: [u8; 6],
+}
+
+impl PayloadBuilder {
+    /// Constructs a new `PayloadBuilder`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a `PayloadBuilder` of the example payload of the data format 5 specification:
+    /// 24.3 °C, 53.49 %, 100044 Pa, acceleration (4, -4, 1036) mG, 2977 mV, 4 dBm, 66
+    /// movements, measurement number 205 and mac address `CB:B8:33:4C:88:4F`.
+    pub fn spec_example() -> Self {
+        Self::new()
+            .temperature(24.3)
+            .humidity(53.49)
+            .pressure(100044)
+            .acceleration(4, -4, 1036)
+            .battery_voltage(2977)
+            .tx_power(4)
+            .movement_counter(66)
+            .measurement_number(205)
+            .mac([0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F])
+    }
+
+    /// Sets the temperature in celsius, clamped to -163.835 ... 163.835 °C.
+    pub fn temperature(mut self, celsius: f64) -> Self {
+        // The float to integer cast saturates, the clamp keeps clear of `INVALID_TEMPERATURE`.
+        self.temperature = ((celsius / 0.005).round() as i16).max(INVALID_TEMPERATURE + 1);
+        self
+    }
+
+    /// Sets the relative humidity in percent, clamped to 0 ... 163.835 %.
+    pub fn humidity(mut self, percent: f64) -> Self {
+        self.humidity = ((percent * 400_f64).round() as u16).min(INVALID_HUMIDITY - 1);
+        self
+    }
+
+    /// Sets the atmospheric pressure in Pa, clamped to 50000 ... 115534 Pa.
+    pub fn pressure(mut self, pa: u32) -> Self {
+        let offset = pa.saturating_sub(PRESSURE_OFFSET_PA);
+        self.pressure = offset.min(u32::from(INVALID_PRESSURE - 1)) as u16;
+        self
+    }
+
+    /// Sets the acceleration in mG.
+    pub fn acceleration(mut self, x: i16, y: i16, z: i16) -> Self {
+        self.acceleration = (x, y, z);
+        self
+    }
+
+    /// Sets the battery voltage in mV, clamped to 1600 ... 3646 mV.
+    pub fn battery_voltage(mut self, mv: u16) -> Self {
+        self.battery = mv.saturating_sub(BATTERY_OFFSET).min(MAX_BATTERY);
+        self
+    }
+
+    /// Sets the transmit power in dBm, clamped to -40 ... 20 dBm.
+    pub fn tx_power(mut self, dbm: i8) -> Self {
+        let steps = (i16::from(dbm) - i16::from(TX_POWER_OFFSET)) / 2;
+        self.tx_power = steps.clamp(0, MAX_TX_POWER) as u16;
+        self
+    }
+
+    /// Sets the movement counter.
+    pub fn movement_counter(mut self, movement_counter: u8) -> Self {
+        self.movement_counter = movement_counter;
+        self
+    }
+
+    /// Sets the measurement sequence number.
+    pub fn measurement_number(mut self, measurement_number: u16) -> Self {
+        self.measurement_number = measurement_number;
+        self
+    }
+
+    /// Sets the mac address.
+    pub fn mac(mut self, mac: [u8; 6]) -> Self {
+        self.mac = mac;
+        self
+    }
+
+    /// Encodes `field` as not available, like a ruuvitag with a faulty sensor does.
+    pub fn not_available(mut self, field: SensorField) -> Self {
+        match field {
+            SensorField::Temperature => self.temperature = INVALID_TEMPERATURE,
+            SensorField::Humidity => self.humidity = INVALID_HUMIDITY,
+            SensorField::Pressure => self.pressure = INVALID_PRESSURE,
+            SensorField::Acceleration => {
+                self.acceleration = (
+                    INVALID_ACCELERATION,
+                    INVALID_ACCELERATION,
+                    INVALID_ACCELERATION,
+                )
+            }
+        }
+        self
+    }
+
+    /// Returns the 24 byte data format 5 payload.
+    pub fn build(&self) -> [u8; 24] {
+        self.sensor_data().to_raw_bytes()
+    }
+
+    /// Returns the reading the payload decodes to.
+    pub fn sensor_data(&self) -> SensorDataV5 {
+        let (x, y, z) = self.acceleration;
+        SensorDataV5::new(
+            self.temperature,
+            self.humidity,
+            self.pressure,
+            Acceleration::new(x, y, z),
+            self.battery << 5 | self.tx_power,
+            self.movement_counter,
+            self.measurement_number,
+            self.mac,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::ruuvitag::{SensorDataV5, SensorField};
+    use crate::testing::PayloadBuilder;
+
+    #[test]
+    fn test_payload_builder_spec_example() {
+        let payload = PayloadBuilder::new()
+            .temperature(24.3)
+            .humidity(53.49)
+            .pressure(100044)
+            .acceleration(4, -4, 1036)
+            .battery_voltage(2977)
+            .tx_power(4)
+            .movement_counter(66)
+            .measurement_number(205)
+            .mac([0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F])
+            .build();
+        assert_eq!(
+            payload,
+            [
+                0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC,
+                0x36, 0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F,
+            ]
+        );
+        let sensor_data = SensorDataV5::from_raw_bytes(&payload).unwrap();
+        assert_eq!(sensor_data.get_battery_voltage(), 2977);
+        assert_eq!(sensor_data.get_tx_power(), 4);
+        assert_eq!(PayloadBuilder::spec_example().build(), payload);
+        assert_eq!(PayloadBuilder::spec_example().sensor_data(), sensor_data);
+    }
+
+    #[test]
+    fn test_payload_builder_clamps_out_of_range_values() {
+        let sensor_data = PayloadBuilder::new()
+            .temperature(-200.0)
+            .humidity(200.0)
+            .pressure(0)
+            .battery_voltage(0)
+            .tx_power(i8::MIN)
+            .sensor_data();
+        assert_eq!(sensor_data.temperature_in_celcius(), -163.835);
+        assert_eq!(sensor_data.get_humidity(), 163.835);
+        assert_eq!(sensor_data.get_pressure(), 50000);
+        assert_eq!(sensor_data.get_battery_voltage(), 1600);
+        assert_eq!(sensor_data.get_tx_power(), -40);
+
+        let sensor_data = PayloadBuilder::new()
+            .temperature(200.0)
+            .pressure(u32::MAX)
+            .battery_voltage(u16::MAX)
+            .tx_power(i8::MAX)
+            .sensor_data();
+        assert_eq!(sensor_data.temperature_in_celcius(), 163.835);
+        assert_eq!(sensor_data.get_pressure(), 115534);
+        assert_eq!(sensor_data.get_battery_voltage(), 3646);
+        assert_eq!(sensor_data.get_tx_power(), 20);
+        for field in [
+            SensorField::Temperature,
+            SensorField::Humidity,
+            SensorField::Pressure,
+        ] {
+            assert!(sensor_data.is_valid(field));
+        }
+
+        // Values just past the end of the range don't spill into the neighbouring fields.
+        let sensor_data = PayloadBuilder::new()
+            .pressure(115536)
+            .battery_voltage(3648)
+            .tx_power(88)
+            .sensor_data();
+        assert_eq!(sensor_data.get_pressure(), 115534);
+        assert_eq!(sensor_data.get_battery_voltage(), 3646);
+        assert_eq!(sensor_data.get_tx_power(), 20);
+    }
+
+    #[test]
+    fn test_payload_builder_not_available() {
+        let sensor_data = PayloadBuilder::spec_example()
+            .not_available(SensorField::Temperature)
+            .not_available(SensorField::Acceleration)
+            .sensor_data();
+        assert!(!sensor_data.is_valid(SensorField::Temperature));
+        assert!(!sensor_data.is_valid(SensorField::Acceleration));
+        assert!(sensor_data.is_valid(SensorField::Humidity));
+        assert!(sensor_data.is_valid(SensorField::Pressure));
+    }
+}
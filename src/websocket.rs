@@ -0,0 +1,149 @@
+//! Module to assist in publishing ruuvitag data to WebSocket clients.
+//!
+//! Available with the `websocket` feature.
+//!
+//! Examples:
+//! ```
+//! use ruuviscanner::ruuvitag::subscribe_ruuvitag;
+//! use ruuviscanner::websocket::WebSocketPublisher;
+//!
+//! let publisher = WebSocketPublisher::bind("0.0.0.0:8080").await?;
+//! // Replace with your mac address.
+//! let mac = "CC:6F:70:EE:4C:AD";
+//! let mut rx = subscribe_ruuvitag(mac).await?;
+//! loop {
+//!     let current_sensor_data: SensorDataV5 = rx.recv().await.unwrap();
+//!     publisher.publish(&current_sensor_data);
+//! }
+//! ```
+use crate::error::RuuviError;
+use crate::ruuvitag::SensorDataV5;
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Number of readings buffered per client before a slow client starts skipping readings.
+const CLIENT_BUFFER: usize = 16;
+
+/// Time to wait before accepting clients again after accepting one failed.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Serves ruuvitag readings as JSON text messages to connected WebSocket clients.
+///
+/// Every published reading is broadcast to all of the connected clients. Publishing never
+/// blocks: a client that can't keep up skips the readings that didn't fit in its buffer.
+/// Clients are disconnected when the publisher is dropped.
+#[derive(Debug)]
+pub struct WebSocketPublisher {
+    readings: broadcast::Sender<String>,
+    local_addr: SocketAddr,
+    accept_task: JoinHandle<()>,
+}
+
+impl WebSocketPublisher {
+    /// Starts listening for WebSocket clients on `addr`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let publisher = WebSocketPublisher::bind("0.0.0.0:8080").await?;
+    /// ```
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> Result<Self, RuuviError> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let (readings, _) = broadcast::channel(CLIENT_BUFFER);
+        let clients = readings.clone();
+        let accept_task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(_) => {
+                        // Errors such as running out of file descriptors persist, don't spin on
+                        // them.
+                        tokio::time::sleep(ACCEPT_BACKOFF).await;
+                        continue;
+                    }
+                };
+                tokio::spawn(serve_client(stream, clients.subscribe()));
+            }
+        });
+        Ok(Self {
+            readings,
+            local_addr,
+            accept_task,
+        })
+    }
+
+    /// Returns the address the publisher is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Publishes the sensor data to all of the connected clients.
+    ///
+    /// Returns the number of clients the reading was sent to.
+    pub fn publish(&self, sensor_data: &SensorDataV5) -> usize {
+        self.readings.send(sensor_data.to_json()).unwrap_or(0)
+    }
+}
+
+impl Drop for WebSocketPublisher {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+/// Forwards readings to a single client until either side closes the connection.
+async fn serve_client(stream: TcpStream, mut readings: broadcast::Receiver<String>) {
+    let websocket = match tokio_tungstenite::accept_async(stream).await {
+        Ok(websocket) => websocket,
+        Err(_) => return,
+    };
+    let (mut write, mut read) = websocket.split();
+    loop {
+        tokio::select! {
+            reading = readings.recv() => match reading {
+                Ok(json) => {
+                    if write.send(Message::Text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                // The client is too slow, skip the readings it missed.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            message = read.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::testing::PayloadBuilder;
+    use crate::websocket::WebSocketPublisher;
+    use futures_util::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[tokio::test]
+    async fn test_websocket_publisher_sends_json() {
+        let publisher = WebSocketPublisher::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", publisher.local_addr());
+        let (mut client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        let sensor_data = PayloadBuilder::spec_example().sensor_data();
+        assert_eq!(publisher.publish(&sensor_data), 1);
+        assert_eq!(
+            client.next().await.unwrap().unwrap(),
+            Message::Text(sensor_data.to_json())
+        );
+    }
+}